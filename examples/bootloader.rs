@@ -0,0 +1,47 @@
+//! Minimal dual-bank bootloader
+//!
+//! Link it in the first 16 KB of the flash (`FLASH : ORIGIN = 0x00000000, LENGTH = 16K` in
+//! `memory.x`). The applications are linked at the vector table address of their slot
+//! (0x00004200 for slot A, 0x00020200 for slot B) and written with `FlashControl::start_update`.
+
+#![no_main]
+#![no_std]
+
+use panic_halt as _;
+use cortex_m_rt::entry;
+
+use msp432p401r as pac;
+use msp432p401r_hal as hal;
+
+use hal::crc::{Crc32Hasher, SoftCrc32};
+use hal::flash::update::{self, Slot, SlotStatus};
+use hal::watchdog::*;
+
+#[entry]
+fn main() -> ! {
+
+    // Take the Peripherals
+    let p = pac::Peripherals::take().unwrap();
+
+    // The CRC32 checks take longer than the reset watchdog interval
+    let _watchdog = p.WDT_A.constrain().disable().unwrap();
+
+    let mut crc = Crc32Hasher::new(SoftCrc32::new());
+
+    // Bootable image with a valid CRC32
+    let mut bootable = |slot: Slot| -> Option<SlotStatus> {
+        let status = slot.status(&mut crc).ok()?;
+        slot.verify(&status.header, &mut crc).ok()?;
+        Some(status)
+    };
+
+    let a = bootable(Slot::A);
+    let b = bootable(Slot::B);
+
+    match update::select(a, b) {
+        Some(slot) => unsafe { update::boot(slot) },
+        None => loop {
+            cortex_m::asm::wfi();
+        },
+    }
+}
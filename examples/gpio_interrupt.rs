@@ -0,0 +1,66 @@
+#![no_main]
+#![no_std]
+
+use panic_halt as _;
+use cortex_m_rt::entry;
+use cortex_m_semihosting::hprintln;
+
+use msp432p401r as pac;
+use msp432p401r_hal as hal;
+
+use pac::interrupt;
+use hal::gpio::interrupt::{Dispatcher, InterruptPort};
+use hal::{gpio::*, watchdog::*};
+
+static PORT1: Dispatcher = Dispatcher::new(InterruptPort::Port1);
+
+#[entry]
+fn main() -> ! {
+
+    // Take the Peripherals
+    let p = pac::Peripherals::take().unwrap();
+
+    // Watchdog Config.
+    let _watchdog = p.WDT_A.constrain().disable().unwrap();                  // Stop WatchdogTimer
+
+    hprintln!("GPIO Interrupt Example");
+
+    let gpio = p.DIO.split();
+
+    // LED 1 - RED
+    let mut p1_0 = gpio.p1_0.into_output();
+
+    // Buttons S1 and S2 (active low)
+    let mut s1 = gpio.p1_1.into_pull_up_input();
+    let mut s2 = gpio.p1_4.into_pull_up_input();
+
+    s1.trigger_on_edge(Edge::Falling);                                       // S1 - Press only
+    s2.trigger_on_edge(Edge::Falling);
+    PORT1.set_both_edges(4, true);                                           // S2 - Press and release
+
+    PORT1.set_handler(1, |_| {
+        hprintln!("S1 pressed");
+    });
+
+    s1.clear_interrupt_pending_bit();
+    s2.clear_interrupt_pending_bit();
+    s1.enable_interrupt();
+    s2.enable_interrupt();
+
+    unsafe {
+        cortex_m::peripheral::NVIC::unmask(pac::interrupt::PORT1_IRQ);
+        cortex_m::interrupt::enable();
+    }
+
+    loop {
+        if PORT1.take_event(4) {
+            p1_0.toggle().unwrap();
+        }
+        cortex_m::asm::wfi();
+    }
+}
+
+#[interrupt]
+fn PORT1_IRQ() {
+    PORT1.dispatch();
+}
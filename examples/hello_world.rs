@@ -26,7 +26,8 @@ fn main() -> ! {
     // PCM Config.
     let pcm = p.PCM.constrain()                                              // Setup PCM
         .set_vcore(VCoreSel::DcdcVcore1)                                     // Set DCDC Vcore1 -> 48 MHz Clock
-        .freeze();
+        .freeze()
+        .unwrap();
     let _pcm_sel = pcm.get_powermode();                                      // Get the current powermode
 
     // Flash Control Config.
@@ -38,7 +38,8 @@ fn main() -> ! {
 
     let _pcm = p.PCM.constrain()
         .set_vcore(VCoreSel::DcdcVcore1)
-        .freeze();
+        .freeze()
+        .unwrap();
 
     let _flash_control = p.FLCTL.constrain()
         .set_waitstates(FlashWaitStates::_2)
@@ -33,7 +33,8 @@ fn main() -> ! {
     // PCM Config.
     let _pcm = p.PCM.constrain()                                              // Setup PCM
         .set_vcore(VCoreSel::DcdcVcore1)                                     // Set DCDC Vcore1 -> 48 MHz Clock
-        .freeze();
+        .freeze()
+        .unwrap();
 
     // Flash Control Config.
     let _flash_control = p.FLCTL.constrain()                                         // Setup Flash
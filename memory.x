@@ -3,3 +3,6 @@ MEMORY
   FLASH : ORIGIN = 0x00000000, LENGTH = 256K
   RAM : ORIGIN = 0x20000000, LENGTH = 64K
 }
+
+/* SRAM resident functions (`.ramfunc`), a `memory.x` of the application must include it as well */
+INCLUDE ramfunc.x
@@ -0,0 +1,12 @@
+/* Functions placed in `.ramfunc` (#[link_section = ".ramfunc"]) are linked in RAM after `.data`
+   and copied from FLASH with it at startup: cortex-m-rt (0.7.3 and later) moves `__edata` past the
+   sections inserted after `.data`. The FLCTL program, erase and margin read routines of the HAL
+   run from there. */
+SECTIONS
+{
+  .ramfunc : ALIGN(4)
+  {
+    *(.ramfunc .ramfunc.*);
+    . = ALIGN(4);
+  } > RAM AT>FLASH
+} INSERT AFTER .data;
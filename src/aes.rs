@@ -0,0 +1,938 @@
+//! HAL library for AES256 (Advanced Encryption Standard) Accelerator - MSP432P401R
+
+/*
+The AES256 accelerator encrypts and decrypts 128-bit blocks with 128, 192 or 256-bit keys.
+
+AESOPx (AESACTL0) selects the operation:
+
+- 00 --> Encryption with the cipher key;
+- 01 --> Decryption with the cipher key, the last round key is computed for every block;
+- 10 --> Generate the first round key needed for decryption (key expansion) from the cipher key;
+- 11 --> Decryption with the first round key generated by AESOPx = 10;
+
+The key is written as 16-bit words to AESAKEY, AESKEYWR is set once every word has been
+written. Blocks are written as 8 words to AESADIN, the operation starts after the last word and
+AESBUSY stays set until the result can be read from AESADOUT.
+
+CBC, CTR and CFB chaining are implemented on top of the single block operations, so they work
+with any key length and for both encryption and decryption.
+*/
+
+use core::convert::TryInto;
+
+use pac::AES256;
+
+pub const BLOCK_SIZE: usize = 16;
+
+const AES_OP_MASK: u16 = 0xFFFC;
+const AES_KL_MASK: u16 = 0xFFF3;
+const AES_SOFTWARE_RESET: u16 = 0x0080;
+const AES_ERROR_FLAG: u16 = 0x0800;
+const AES_CIPHER_MODE_ENABLE: u16 = 0x8000;
+
+const AES_BUSY: u16 = 0x0001;
+const AES_KEY_WRITTEN: u16 = 0x0002;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// Key is not 16, 24 or 32 bytes long
+    InvalidKeyLength,
+    /// Data length is not a multiple of the block size
+    InvalidDataLength,
+    /// No key was loaded, or the loaded key can not be used for this operation
+    KeyNotSet,
+    /// The accelerator registers were accessed while an operation was in progress (AESERRFG)
+    Access,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum KeyLength {
+    Aes128 = 0x0000,
+    Aes192 = 0x0004,
+    Aes256 = 0x0008,
+}
+
+impl KeyLength {
+    fn from_key(key: &[u8]) -> Result<Self, Error> {
+        match key.len() {
+            16 => Ok(KeyLength::Aes128),
+            24 => Ok(KeyLength::Aes192),
+            32 => Ok(KeyLength::Aes256),
+            _ => Err(Error::InvalidKeyLength),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Operation {
+    Encrypt = 0x00,
+    Decrypt = 0x01,
+    GenerateDecryptionKey = 0x02,
+    DecryptWithDecryptionKey = 0x03,
+}
+
+/// Key currently loaded in the accelerator
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LoadedKey {
+    None,
+    /// Cipher key, usable for encryption and (slower) decryption
+    Cipher(KeyLength),
+    /// Expanded first round key, usable for decryption only
+    Decipher(KeyLength),
+}
+
+pub trait AesExt {
+    fn constrain(self) -> Aes;
+}
+
+impl AesExt for AES256 {
+    fn constrain(self) -> Aes {
+        Aes::new(self)
+    }
+}
+
+pub struct Aes {
+    aes: AES256,
+    key: LoadedKey,
+}
+
+impl Aes {
+    fn new(aes: AES256) -> Self {
+        let mut aes = Aes { aes, key: LoadedKey::None };
+        aes.reset();
+        aes
+    }
+
+    #[inline]
+    pub fn loaded_key(&self) -> LoadedKey {
+        self.key
+    }
+
+    /// Load the cipher key used for encryption (and decryption with AESOPx = 01)
+    pub fn set_encryption_key(&mut self, key: &[u8]) -> Result<&mut Self, Error> {
+        let length = KeyLength::from_key(key)?;
+
+        self.set_operation(Operation::Encrypt);
+        self.write_key(key, length);
+        self.key = LoadedKey::Cipher(length);
+        Ok(self)
+    }
+
+    /// Expand the cipher key into the first round key, making further decryptions faster
+    pub fn set_decryption_key(&mut self, key: &[u8]) -> Result<&mut Self, Error> {
+        let length = KeyLength::from_key(key)?;
+
+        self.set_operation(Operation::GenerateDecryptionKey);
+        self.write_key(key, length);
+        self.wait_busy();
+        self.set_operation(Operation::DecryptWithDecryptionKey);
+
+        if let Err(error) = self.check_error() {
+            self.key = LoadedKey::None;
+            return Err(error);
+        }
+
+        self.key = LoadedKey::Decipher(length);
+        Ok(self)
+    }
+
+    /// Encrypt one block in place
+    pub fn encrypt_block(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error> {
+        match self.key {
+            LoadedKey::Cipher(_) => self.set_operation(Operation::Encrypt),
+            _ => return Err(Error::KeyNotSet),
+        }
+
+        self.process_block(block)
+    }
+
+    /// Decrypt one block in place
+    pub fn decrypt_block(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error> {
+        match self.key {
+            LoadedKey::Cipher(_) => self.set_operation(Operation::Decrypt),
+            LoadedKey::Decipher(_) => self.set_operation(Operation::DecryptWithDecryptionKey),
+            LoadedKey::None => return Err(Error::KeyNotSet),
+        }
+
+        self.process_block(block)
+    }
+
+    /// Encrypt `data` in place with ECB, `data` must be a multiple of the block size
+    pub fn ecb_encrypt(&mut self, data: &mut [u8]) -> Result<(), Error> {
+        ecb_encrypt(self, data)
+    }
+
+    /// Decrypt `data` in place with ECB, `data` must be a multiple of the block size
+    pub fn ecb_decrypt(&mut self, data: &mut [u8]) -> Result<(), Error> {
+        ecb_decrypt(self, data)
+    }
+
+    /// Encrypt `data` in place with CBC, `iv` is updated to chain the next call
+    pub fn cbc_encrypt(&mut self, iv: &mut [u8; BLOCK_SIZE], data: &mut [u8]) -> Result<(), Error> {
+        cbc_encrypt(self, iv, data)
+    }
+
+    /// Decrypt `data` in place with CBC, `iv` is updated to chain the next call
+    pub fn cbc_decrypt(&mut self, iv: &mut [u8; BLOCK_SIZE], data: &mut [u8]) -> Result<(), Error> {
+        cbc_decrypt(self, iv, data)
+    }
+
+    /// Encrypt or decrypt `data` of any length in place with CTR.
+    ///
+    /// `counter` is incremented as a 128-bit big endian number for every block. A partial last
+    /// block consumes a full counter value.
+    pub fn ctr_apply(&mut self, counter: &mut [u8; BLOCK_SIZE], data: &mut [u8]) -> Result<(), Error> {
+        ctr_apply(self, counter, data)
+    }
+
+    /// Encrypt `data` of any length in place with CFB-128, `iv` is updated to chain the next call.
+    ///
+    /// Only a call over a multiple of the block size can be chained, a partial last chunk ends
+    /// the stream.
+    pub fn cfb_encrypt(&mut self, iv: &mut [u8; BLOCK_SIZE], data: &mut [u8]) -> Result<(), Error> {
+        cfb_encrypt(self, iv, data)
+    }
+
+    /// Decrypt `data` of any length in place with CFB-128, `iv` is updated to chain the next call.
+    ///
+    /// Only a call over a multiple of the block size can be chained, a partial last chunk ends
+    /// the stream.
+    pub fn cfb_decrypt(&mut self, iv: &mut [u8; BLOCK_SIZE], data: &mut [u8]) -> Result<(), Error> {
+        cfb_decrypt(self, iv, data)
+    }
+
+    /// Reset the accelerator, discarding the loaded key
+    pub fn reset(&mut self) {
+        self.aes.aesactl0.write(|w| unsafe { w.bits(AES_SOFTWARE_RESET) });
+        self.key = LoadedKey::None;
+    }
+
+    fn process_block(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error> {
+        self.wait_busy();
+
+        for word in block.chunks(2) {
+            self.aes.aesadin.write(|w| unsafe {
+                w.bits(u16::from_le_bytes([word[0], word[1]]))
+            });
+        }
+
+        self.wait_busy();
+
+        for word in block.chunks_mut(2) {
+            // AESADOUT is described as write-only by the PAC, although it is the output register
+            let data = unsafe {
+                core::ptr::read_volatile(&self.aes.aesadout as *const _ as *const u16)
+            }.to_le_bytes();
+            word.copy_from_slice(&data);
+        }
+
+        self.check_error()
+    }
+
+    fn write_key(&self, key: &[u8], length: KeyLength) {
+        self.wait_busy();
+        self.aes.aesactl0.modify(|r, w| unsafe {
+            w.bits((r.bits() & AES_KL_MASK & !AES_CIPHER_MODE_ENABLE) | length as u16)
+        });
+
+        for word in key.chunks(2) {
+            self.aes.aesakey.write(|w| unsafe {
+                w.bits(u16::from_le_bytes([word[0], word[1]]))
+            });
+        }
+
+        while self.aes.aesastat.read().bits() & AES_KEY_WRITTEN == 0 {}
+    }
+
+    fn set_operation(&self, operation: Operation) {
+        self.wait_busy();
+        self.aes.aesactl0.modify(|r, w| unsafe {
+            w.bits((r.bits() & AES_OP_MASK) | operation as u16)
+        });
+    }
+
+    fn check_error(&self) -> Result<(), Error> {
+        if self.aes.aesactl0.read().bits() & AES_ERROR_FLAG != 0 {
+            self.aes.aesactl0.modify(|r, w| unsafe { w.bits(r.bits() & !AES_ERROR_FLAG) });
+            Err(Error::Access)
+        } else {
+            Ok(())
+        }
+    }
+
+    #[inline]
+    fn wait_busy(&self) {
+        while self.aes.aesastat.read().bits() & AES_BUSY != 0 {}
+    }
+}
+
+/// Single block operations the chaining modes are built on
+trait BlockOps {
+    fn encrypt(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error>;
+    fn decrypt(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error>;
+}
+
+impl BlockOps for Aes {
+    #[inline]
+    fn encrypt(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error> {
+        self.encrypt_block(block)
+    }
+
+    #[inline]
+    fn decrypt(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error> {
+        self.decrypt_block(block)
+    }
+}
+
+fn ecb_encrypt(cipher: &mut impl BlockOps, data: &mut [u8]) -> Result<(), Error> {
+    for block in blocks(data)? {
+        cipher.encrypt(block)?;
+    }
+    Ok(())
+}
+
+fn ecb_decrypt(cipher: &mut impl BlockOps, data: &mut [u8]) -> Result<(), Error> {
+    for block in blocks(data)? {
+        cipher.decrypt(block)?;
+    }
+    Ok(())
+}
+
+fn cbc_encrypt(
+    cipher: &mut impl BlockOps,
+    iv: &mut [u8; BLOCK_SIZE],
+    data: &mut [u8],
+) -> Result<(), Error> {
+    for block in blocks(data)? {
+        xor(block, iv);
+        cipher.encrypt(block)?;
+        iv.copy_from_slice(block);
+    }
+    Ok(())
+}
+
+fn cbc_decrypt(
+    cipher: &mut impl BlockOps,
+    iv: &mut [u8; BLOCK_SIZE],
+    data: &mut [u8],
+) -> Result<(), Error> {
+    for block in blocks(data)? {
+        let cipher_text = *block;
+        cipher.decrypt(block)?;
+        xor(block, iv);
+        *iv = cipher_text;
+    }
+    Ok(())
+}
+
+fn ctr_apply(
+    cipher: &mut impl BlockOps,
+    counter: &mut [u8; BLOCK_SIZE],
+    data: &mut [u8],
+) -> Result<(), Error> {
+    for chunk in data.chunks_mut(BLOCK_SIZE) {
+        let mut keystream = *counter;
+        cipher.encrypt(&mut keystream)?;
+        xor(chunk, &keystream);
+        increment_counter(counter);
+    }
+    Ok(())
+}
+
+fn cfb_encrypt(
+    cipher: &mut impl BlockOps,
+    iv: &mut [u8; BLOCK_SIZE],
+    data: &mut [u8],
+) -> Result<(), Error> {
+    for chunk in data.chunks_mut(BLOCK_SIZE) {
+        cipher.encrypt(iv)?;
+        xor(chunk, iv);
+        iv[..chunk.len()].copy_from_slice(chunk);
+    }
+    Ok(())
+}
+
+fn cfb_decrypt(
+    cipher: &mut impl BlockOps,
+    iv: &mut [u8; BLOCK_SIZE],
+    data: &mut [u8],
+) -> Result<(), Error> {
+    for chunk in data.chunks_mut(BLOCK_SIZE) {
+        let mut keystream = *iv;
+        cipher.encrypt(&mut keystream)?;
+        iv[..chunk.len()].copy_from_slice(chunk);
+        xor(chunk, &keystream);
+    }
+    Ok(())
+}
+
+fn blocks(data: &mut [u8]) -> Result<impl Iterator<Item = &mut [u8; BLOCK_SIZE]>, Error> {
+    if data.len() % BLOCK_SIZE != 0 {
+        return Err(Error::InvalidDataLength);
+    }
+
+    Ok(data.chunks_exact_mut(BLOCK_SIZE).map(|block| {
+        let block: &mut [u8; BLOCK_SIZE] = block.try_into().unwrap();
+        block
+    }))
+}
+
+#[inline]
+fn xor(data: &mut [u8], other: &[u8; BLOCK_SIZE]) {
+    for (byte, other) in data.iter_mut().zip(other.iter()) {
+        *byte ^= other;
+    }
+}
+
+#[inline]
+fn increment_counter(counter: &mut [u8; BLOCK_SIZE]) {
+    for byte in counter.iter_mut().rev() {
+        *byte = byte.wrapping_add(1);
+        if *byte != 0 {
+            break;
+        }
+    }
+}
+
+/// RustCrypto `cipher` traits, so the accelerator can be used by the `cbc`, `ctr`, `cfb-mode`...
+/// crates. The key must be loaded beforehand, processing a block without a usable key panics.
+#[cfg(feature = "cipher")]
+mod block_cipher {
+    use cipher::consts::{U1, U16};
+    use cipher::inout::InOut;
+    use cipher::{Block, BlockBackend, BlockCipher, BlockClosure, BlockDecryptMut, BlockEncryptMut,
+                 BlockSizeUser, ParBlocksSizeUser};
+
+    use super::{Aes, BLOCK_SIZE};
+
+    impl BlockSizeUser for Aes {
+        type BlockSize = U16;
+    }
+
+    impl BlockCipher for Aes {}
+
+    struct Encryptor<'a>(&'a mut Aes);
+    struct Decryptor<'a>(&'a mut Aes);
+
+    impl BlockSizeUser for Encryptor<'_> {
+        type BlockSize = U16;
+    }
+
+    impl ParBlocksSizeUser for Encryptor<'_> {
+        type ParBlocksSize = U1;
+    }
+
+    impl BlockBackend for Encryptor<'_> {
+        fn proc_block(&mut self, mut block: InOut<'_, '_, Block<Self>>) {
+            let mut data = [0u8; BLOCK_SIZE];
+            data.copy_from_slice(block.get_in());
+            self.0.encrypt_block(&mut data).expect("AES encryption failed");
+            block.get_out().copy_from_slice(&data);
+        }
+    }
+
+    impl BlockSizeUser for Decryptor<'_> {
+        type BlockSize = U16;
+    }
+
+    impl ParBlocksSizeUser for Decryptor<'_> {
+        type ParBlocksSize = U1;
+    }
+
+    impl BlockBackend for Decryptor<'_> {
+        fn proc_block(&mut self, mut block: InOut<'_, '_, Block<Self>>) {
+            let mut data = [0u8; BLOCK_SIZE];
+            data.copy_from_slice(block.get_in());
+            self.0.decrypt_block(&mut data).expect("AES decryption failed");
+            block.get_out().copy_from_slice(&data);
+        }
+    }
+
+    impl BlockEncryptMut for Aes {
+        fn encrypt_with_backend_mut(&mut self, f: impl BlockClosure<BlockSize = U16>) {
+            f.call(&mut Encryptor(self))
+        }
+    }
+
+    impl BlockDecryptMut for Aes {
+        fn decrypt_with_backend_mut(&mut self, f: impl BlockClosure<BlockSize = U16>) {
+            f.call(&mut Decryptor(self))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Invertible stand-in for the accelerator: rotate the block by one byte, then XOR a key
+    struct Toy;
+
+    const KEY: [u8; BLOCK_SIZE] = [
+        0x2B, 0x7E, 0x15, 0x16, 0x28, 0xAE, 0xD2, 0xA6, 0xAB, 0xF7, 0x15, 0x88, 0x09, 0xCF, 0x4F,
+        0x3C,
+    ];
+
+    impl BlockOps for Toy {
+        fn encrypt(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error> {
+            block.rotate_left(1);
+            xor(block, &KEY);
+            Ok(())
+        }
+
+        fn decrypt(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error> {
+            xor(block, &KEY);
+            block.rotate_right(1);
+            Ok(())
+        }
+    }
+
+    fn encrypted(mut block: [u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+        Toy.encrypt(&mut block).unwrap();
+        block
+    }
+
+    fn xored(mut block: [u8; BLOCK_SIZE], other: &[u8; BLOCK_SIZE]) -> [u8; BLOCK_SIZE] {
+        xor(&mut block, other);
+        block
+    }
+
+    fn block(data: &[u8], index: usize) -> [u8; BLOCK_SIZE] {
+        data[index * BLOCK_SIZE..(index + 1) * BLOCK_SIZE].try_into().unwrap()
+    }
+
+    fn plain_text() -> [u8; 3 * BLOCK_SIZE] {
+        let mut data = [0; 3 * BLOCK_SIZE];
+        for (index, byte) in data.iter_mut().enumerate() {
+            *byte = (index as u8).wrapping_mul(37) ^ 0x5A;
+        }
+        data
+    }
+
+    const IV: [u8; BLOCK_SIZE] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D, 0x0E,
+        0x0F,
+    ];
+
+    /// Software AES-128 (FIPS-197), reference for the known-answer tests
+    struct Reference {
+        round_keys: [[u8; BLOCK_SIZE]; 11],
+    }
+
+    fn multiply(mut a: u8, mut b: u8) -> u8 {
+        let mut product = 0;
+        while b != 0 {
+            if b & 1 != 0 {
+                product ^= a;
+            }
+            a = (a << 1) ^ if a & 0x80 != 0 { 0x1B } else { 0 };
+            b >>= 1;
+        }
+        product
+    }
+
+    fn sbox(byte: u8) -> u8 {
+        // Multiplicative inverse (a^254), then the affine transformation
+        let mut inverse = 1;
+        for _ in 0..254 {
+            inverse = multiply(inverse, byte);
+        }
+        let rotations = (1..5).fold(inverse, |value, shift| value ^ inverse.rotate_left(shift));
+        rotations ^ 0x63
+    }
+
+    fn inverse_sbox(byte: u8) -> u8 {
+        (0..=255).find(|&value| sbox(value) == byte).unwrap()
+    }
+
+    impl Reference {
+        fn new(key: &[u8; BLOCK_SIZE]) -> Self {
+            let mut round_keys = [*key; 11];
+            let mut rcon = 1;
+
+            for round in 1..11 {
+                let previous = round_keys[round - 1];
+                let mut word = [previous[13], previous[14], previous[15], previous[12]];
+                for byte in word.iter_mut() {
+                    *byte = sbox(*byte);
+                }
+                word[0] ^= rcon;
+                rcon = multiply(rcon, 2);
+
+                for index in 0..BLOCK_SIZE {
+                    let value = previous[index] ^ word[index % 4];
+                    round_keys[round][index] = value;
+                    word[index % 4] = value;
+                }
+            }
+
+            Reference { round_keys }
+        }
+
+        fn shift_rows(block: &mut [u8; BLOCK_SIZE], inverse: bool) {
+            let state = *block;
+            for column in 0..4 {
+                for row in 0..4 {
+                    let source = if inverse { column + 4 - row } else { column + row } % 4;
+                    block[4 * column + row] = state[4 * source + row];
+                }
+            }
+        }
+
+        fn mix_columns(block: &mut [u8; BLOCK_SIZE], factors: [u8; 4]) {
+            for column in block.chunks_exact_mut(4) {
+                let state = [column[0], column[1], column[2], column[3]];
+                for (row, byte) in column.iter_mut().enumerate() {
+                    *byte = (0..4).fold(0, |value, index| {
+                        value ^ multiply(factors[(index + 4 - row) % 4], state[index])
+                    });
+                }
+            }
+        }
+    }
+
+    impl BlockOps for Reference {
+        fn encrypt(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error> {
+            xor(block, &self.round_keys[0]);
+            for round in 1..11 {
+                for byte in block.iter_mut() {
+                    *byte = sbox(*byte);
+                }
+                Self::shift_rows(block, false);
+                if round != 10 {
+                    Self::mix_columns(block, [2, 3, 1, 1]);
+                }
+                xor(block, &self.round_keys[round]);
+            }
+            Ok(())
+        }
+
+        fn decrypt(&mut self, block: &mut [u8; BLOCK_SIZE]) -> Result<(), Error> {
+            for round in (1..11).rev() {
+                xor(block, &self.round_keys[round]);
+                if round != 10 {
+                    Self::mix_columns(block, [14, 11, 13, 9]);
+                }
+                Self::shift_rows(block, true);
+                for byte in block.iter_mut() {
+                    *byte = inverse_sbox(*byte);
+                }
+            }
+            xor(block, &self.round_keys[0]);
+            Ok(())
+        }
+    }
+
+    fn hex<const N: usize>(text: &str) -> [u8; N] {
+        let mut bytes = [0; N];
+        for (index, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&text[2 * index..2 * index + 2], 16).unwrap();
+        }
+        bytes
+    }
+
+    /// SP 800-38A, appendix F: AES-128 key and plaintext of every mode example
+    const SP800_38A_KEY: &str = "2b7e151628aed2a6abf7158809cf4f3c";
+    const SP800_38A_PLAIN: &str = concat!(
+        "6bc1bee22e409f96e93d7e117393172a",
+        "ae2d8a571e03ac9c9eb76fac45af8e51",
+        "30c81c46a35ce411e5fbc1191a0a52ef",
+        "f69f2445df4f9b17ad2b417be66c3710",
+    );
+
+    fn known_answer(
+        apply: impl Fn(&mut Reference, &mut [u8; BLOCK_SIZE], &mut [u8]) -> Result<(), Error>,
+        iv: &str,
+        expected: &str,
+    ) {
+        let mut cipher = Reference::new(&hex(SP800_38A_KEY));
+        let mut data: [u8; 4 * BLOCK_SIZE] = hex(SP800_38A_PLAIN);
+        let mut iv = hex(iv);
+
+        apply(&mut cipher, &mut iv, &mut data).unwrap();
+        assert_eq!(data, hex::<{ 4 * BLOCK_SIZE }>(expected));
+    }
+
+    #[test]
+    fn fips197_block() {
+        // FIPS-197, appendix C.1
+        let mut cipher = Reference::new(&hex("000102030405060708090a0b0c0d0e0f"));
+        let mut block = hex("00112233445566778899aabbccddeeff");
+
+        cipher.encrypt(&mut block).unwrap();
+        assert_eq!(block, hex::<BLOCK_SIZE>("69c4e0d86a7b0430d8cdb78070b4c55a"));
+        cipher.decrypt(&mut block).unwrap();
+        assert_eq!(block, hex::<BLOCK_SIZE>("00112233445566778899aabbccddeeff"));
+
+        // FIPS-197, appendix A.1: last round key of the expansion
+        let cipher = Reference::new(&hex(SP800_38A_KEY));
+        assert_eq!(cipher.round_keys[10], hex::<BLOCK_SIZE>("d014f9a8c9ee2589e13f0cc8b6630ca6"));
+    }
+
+    #[test]
+    fn key_lengths() {
+        assert_eq!(KeyLength::from_key(&[0; 16]), Ok(KeyLength::Aes128));
+        assert_eq!(KeyLength::from_key(&[0; 24]), Ok(KeyLength::Aes192));
+        assert_eq!(KeyLength::from_key(&[0; 32]), Ok(KeyLength::Aes256));
+
+        for length in [0, 8, 15, 17, 31, 33] {
+            assert_eq!(KeyLength::from_key(&[0; 33][..length]), Err(Error::InvalidKeyLength));
+        }
+    }
+
+    #[test]
+    fn sp800_38a_ecb() {
+        // F.1.1
+        let expected = concat!(
+            "3ad77bb40d7a3660a89ecaf32466ef97",
+            "f5d3d58503b9699de785895a96fdbaaf",
+            "43b1cd7f598ece23881b00e3ed030688",
+            "7b0c785e27e8ad3f8223207104725dd4",
+        );
+        let no_iv = "00000000000000000000000000000000";
+        known_answer(|cipher, _, data| ecb_encrypt(cipher, data), no_iv, expected);
+
+        // F.1.2
+        let mut cipher = Reference::new(&hex(SP800_38A_KEY));
+        let mut data: [u8; 4 * BLOCK_SIZE] = hex(expected);
+        ecb_decrypt(&mut cipher, &mut data).unwrap();
+        assert_eq!(data, hex::<{ 4 * BLOCK_SIZE }>(SP800_38A_PLAIN));
+    }
+
+    #[test]
+    fn sp800_38a_cbc() {
+        // F.2.1, chained over two calls
+        known_answer(
+            |cipher, iv, data| {
+                cbc_encrypt(cipher, iv, &mut data[..BLOCK_SIZE])?;
+                cbc_encrypt(cipher, iv, &mut data[BLOCK_SIZE..])?;
+                assert_eq!(*iv, hex::<BLOCK_SIZE>("3ff1caa1681fac09120eca307586e1a7"));
+                Ok(())
+            },
+            "000102030405060708090a0b0c0d0e0f",
+            concat!(
+                "7649abac8119b246cee98e9b12e9197d",
+                "5086cb9b507219ee95db113a917678b2",
+                "73bed6b8e3c1743b7116e69e22229516",
+                "3ff1caa1681fac09120eca307586e1a7",
+            ),
+        );
+
+        // F.2.2
+        let mut cipher = Reference::new(&hex(SP800_38A_KEY));
+        let mut iv = hex("000102030405060708090a0b0c0d0e0f");
+        let mut data: [u8; BLOCK_SIZE] = hex("7649abac8119b246cee98e9b12e9197d");
+        cbc_decrypt(&mut cipher, &mut iv, &mut data).unwrap();
+        assert_eq!(data, hex::<BLOCK_SIZE>("6bc1bee22e409f96e93d7e117393172a"));
+    }
+
+    #[test]
+    fn sp800_38a_ctr() {
+        // F.5.1 (F.5.2 is the same operation)
+        known_answer(
+            |cipher, counter, data| {
+                ctr_apply(cipher, counter, data)?;
+                assert_eq!(*counter, hex::<BLOCK_SIZE>("f0f1f2f3f4f5f6f7f8f9fafbfcfdff03"));
+                Ok(())
+            },
+            "f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff",
+            concat!(
+                "874d6191b620e3261bef6864990db6ce",
+                "9806f66b7970fdff8617187bb9fffdff",
+                "5ae4df3edbd5d35e5b4f09020db03eab",
+                "1e031dda2fbe03d1792170a0f3009cee",
+            ),
+        );
+    }
+
+    #[test]
+    fn sp800_38a_cfb() {
+        let expected = concat!(
+            "3b3fd92eb72dad20333449f8e83cfb4a",
+            "c8a64537a0b3a93fcde3cdad9f1ce58b",
+            "26751f67a3cbb140b1808cf187a4f4df",
+            "c04b05357c5d1c0eeac4c66f9ff7f2e6",
+        );
+        // F.3.13
+        known_answer(cfb_encrypt, "000102030405060708090a0b0c0d0e0f", expected);
+
+        // F.3.14
+        let mut cipher = Reference::new(&hex(SP800_38A_KEY));
+        let mut iv = hex("000102030405060708090a0b0c0d0e0f");
+        let mut data: [u8; 4 * BLOCK_SIZE] = hex(expected);
+        cfb_decrypt(&mut cipher, &mut iv, &mut data).unwrap();
+        assert_eq!(data, hex::<{ 4 * BLOCK_SIZE }>(SP800_38A_PLAIN));
+    }
+
+    #[test]
+    fn counter_increment() {
+        let mut counter = [0; BLOCK_SIZE];
+        increment_counter(&mut counter);
+        assert_eq!(counter[15], 0x01);
+        assert!(counter[..15].iter().all(|&byte| byte == 0));
+
+        // Carry across bytes, big endian
+        let mut counter = [0; BLOCK_SIZE];
+        counter[12..].copy_from_slice(&[0x12, 0xFF, 0xFF, 0xFF]);
+        increment_counter(&mut counter);
+        assert_eq!(counter[12..], [0x13, 0x00, 0x00, 0x00]);
+        assert!(counter[..12].iter().all(|&byte| byte == 0));
+
+        // Wraps around to zero
+        let mut counter = [0xFF; BLOCK_SIZE];
+        increment_counter(&mut counter);
+        assert_eq!(counter, [0; BLOCK_SIZE]);
+    }
+
+    #[test]
+    fn block_split() {
+        assert_eq!(blocks(&mut []).map(|blocks| blocks.count()).ok(), Some(0));
+        assert_eq!(blocks(&mut [0; 32]).map(|blocks| blocks.count()).ok(), Some(2));
+
+        for length in [1, 15, 17, 31, 33] {
+            let mut data = [0; 33];
+            assert!(matches!(blocks(&mut data[..length]), Err(Error::InvalidDataLength)));
+        }
+
+        // Every mode requiring full blocks rejects the data untouched
+        let mut data = [0xA5; 20];
+        let mut iv = IV;
+        assert_eq!(ecb_encrypt(&mut Toy, &mut data), Err(Error::InvalidDataLength));
+        assert_eq!(ecb_decrypt(&mut Toy, &mut data), Err(Error::InvalidDataLength));
+        assert_eq!(cbc_encrypt(&mut Toy, &mut iv, &mut data), Err(Error::InvalidDataLength));
+        assert_eq!(cbc_decrypt(&mut Toy, &mut iv, &mut data), Err(Error::InvalidDataLength));
+        assert_eq!((data, iv), ([0xA5; 20], IV));
+    }
+
+    #[test]
+    fn ecb() {
+        let plain = plain_text();
+        let mut data = plain;
+
+        ecb_encrypt(&mut Toy, &mut data).unwrap();
+        for index in 0..3 {
+            assert_eq!(block(&data, index), encrypted(block(&plain, index)));
+        }
+
+        ecb_decrypt(&mut Toy, &mut data).unwrap();
+        assert_eq!(data, plain);
+    }
+
+    #[test]
+    fn cbc() {
+        let plain = plain_text();
+        let mut data = plain;
+        let mut iv = IV;
+
+        cbc_encrypt(&mut Toy, &mut iv, &mut data).unwrap();
+
+        // C0 = E(P0 ^ IV), Cn = E(Pn ^ Cn-1)
+        let mut previous = IV;
+        for index in 0..3 {
+            let cipher_text = encrypted(xored(block(&plain, index), &previous));
+            assert_eq!(block(&data, index), cipher_text);
+            previous = cipher_text;
+        }
+        assert_eq!(iv, previous);
+
+        // Chaining over several calls gives the same result
+        let mut chained = plain;
+        let mut iv = IV;
+        cbc_encrypt(&mut Toy, &mut iv, &mut chained[..BLOCK_SIZE]).unwrap();
+        cbc_encrypt(&mut Toy, &mut iv, &mut chained[BLOCK_SIZE..]).unwrap();
+        assert_eq!(chained, data);
+
+        let mut iv = IV;
+        cbc_decrypt(&mut Toy, &mut iv, &mut data[..2 * BLOCK_SIZE]).unwrap();
+        cbc_decrypt(&mut Toy, &mut iv, &mut data[2 * BLOCK_SIZE..]).unwrap();
+        assert_eq!(data, plain);
+        assert_eq!(iv, previous);
+    }
+
+    #[test]
+    fn ctr() {
+        let plain = plain_text();
+        // A partial last block, and a counter carrying into the upper bytes
+        let mut data = plain;
+        let data = &mut data[..40];
+        let mut counter = [0; BLOCK_SIZE];
+        counter[14..].copy_from_slice(&[0x00, 0xFE]);
+        let start = counter;
+
+        ctr_apply(&mut Toy, &mut counter, data).unwrap();
+
+        let mut expected_counter = start;
+        for (index, chunk) in data.chunks(BLOCK_SIZE).enumerate() {
+            let keystream = encrypted(expected_counter);
+            for (offset, &byte) in chunk.iter().enumerate() {
+                let position = index * BLOCK_SIZE + offset;
+                assert_eq!(byte, plain[position] ^ keystream[offset]);
+            }
+            increment_counter(&mut expected_counter);
+        }
+        assert_eq!(counter[13..], [0x00, 0x01, 0x01]);
+        assert_eq!(counter, expected_counter);
+
+        // The same operation decrypts
+        let mut counter = start;
+        ctr_apply(&mut Toy, &mut counter, data).unwrap();
+        assert_eq!(data[..], plain[..40]);
+    }
+
+    #[test]
+    fn cfb() {
+        let plain = plain_text();
+        let mut data = plain;
+        let data = &mut data[..40];
+        let mut iv = IV;
+
+        cfb_encrypt(&mut Toy, &mut iv, data).unwrap();
+
+        // C0 = P0 ^ E(IV), Cn = Pn ^ E(Cn-1)
+        let mut previous = IV;
+        for index in 0..2 {
+            let cipher_text = xored(block(&plain, index), &encrypted(previous));
+            assert_eq!(block(data, index), cipher_text);
+            previous = cipher_text;
+        }
+        let keystream = encrypted(previous);
+        for (offset, &byte) in keystream[..8].iter().enumerate() {
+            let position = 2 * BLOCK_SIZE + offset;
+            assert_eq!(data[position], plain[position] ^ byte);
+        }
+        // The partial block replaces the start of the IV, the rest is the keystream
+        assert_eq!(iv[..8], data[32..]);
+        assert_eq!(iv[8..], keystream[8..]);
+
+        let mut iv = IV;
+        cfb_decrypt(&mut Toy, &mut iv, &mut data[..BLOCK_SIZE]).unwrap();
+        cfb_decrypt(&mut Toy, &mut iv, &mut data[BLOCK_SIZE..]).unwrap();
+        assert_eq!(data[..], plain[..40]);
+    }
+
+    #[test]
+    fn cfb_partial_chunk() {
+        let plain = plain_text();
+        let mut single = plain;
+        let mut iv = IV;
+        cfb_encrypt(&mut Toy, &mut iv, &mut single).unwrap();
+
+        // A split at a non-block boundary is only valid as the end of the stream
+        let mut data = plain;
+        let mut iv = IV;
+        cfb_encrypt(&mut Toy, &mut iv, &mut data[..20]).unwrap();
+        assert_eq!(data[..20], single[..20]);
+
+        let mut iv = IV;
+        cfb_decrypt(&mut Toy, &mut iv, &mut data[..BLOCK_SIZE]).unwrap();
+        cfb_decrypt(&mut Toy, &mut iv, &mut data[BLOCK_SIZE..20]).unwrap();
+        assert_eq!(data[..20], plain[..20]);
+
+        // Continuing after the partial chunk is neither CFB-128 nor the single call
+        let mut iv = IV;
+        cfb_encrypt(&mut Toy, &mut iv, &mut data[..20]).unwrap();
+        cfb_encrypt(&mut Toy, &mut iv, &mut data[20..]).unwrap();
+        assert_eq!(data[..20], single[..20]);
+        assert_ne!(data[20..], single[20..]);
+    }
+}
@@ -553,20 +553,23 @@ impl ClockConfig<MclkDefined, SmclkDefined> {
             self.cs_key(false);
         });
 
+        self.clocks()
+    }
+
+    /// Clock frequencies this configuration gives once frozen
+    pub fn clocks(&self) -> Clocks {
         let aclk_freq: u32 = self.aclk_sel.freq();
         let mclk_freq = self.mclk.0.freq() >> self.mclk_div as u32;
         let hsmclk_freq: u32 = self.smclk_sel.freq();
         let smclk_freq: u32 = self.smclk_sel.freq() >> self.smclk.0 as u32;
 
-        let clocks = Clocks {
+        Clocks {
             aclk: Hertz(aclk_freq),
             mclk: Hertz(mclk_freq),
             hsmclk: Hertz(hsmclk_freq),
             smclk: Hertz(smclk_freq),
             bclk: Hertz(aclk_freq),
-        };
-
-        clocks
+        }
     }
 }
 
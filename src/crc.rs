@@ -0,0 +1,490 @@
+//! HAL library for CRC32 (Cyclic Redundancy Check) Module - MSP432P401R
+
+/*
+The CRC32 module has two independent signature generators:
+
+- CRC16 --> CRC-CCITT polynomial (x^16 + x^12 + x^5 + 1 = 0x1021);
+- CRC32 --> CRC32-ISO3309 polynomial (0x04C11DB7), the one used by Ethernet and zlib;
+
+Each generator has:
+
+- CRCxDI --> data in, bits processed LSB first (reflected input);
+- CRCxDIRB --> data in reversed, bits processed MSB first (standard input);
+- CRCxINIRES --> seed (on write) and current signature (on read);
+- CRCxRESR --> current signature with its bits reversed;
+
+Words written to the data registers are processed from bit 0 to bit 15 on CRCxDI, and from
+bit 15 to bit 0 on CRCxDIRB. `SoftCrc16` and `SoftCrc32` follow exactly the same rules, so code
+written against `CrcEngine` gives the same signatures with or without the peripheral.
+
+Well known checks over the ASCII string "123456789":
+
+- CRC-16/CCITT-FALSE (seed 0xFFFF, DIRB, INIRES) --> 0x29B1;
+- CRC-32/ISO-HDLC (seed 0xFFFFFFFF, DI, RESR ^ 0xFFFFFFFF) --> 0xCBF43926;
+*/
+
+use core::hash::Hasher;
+
+use pac::CRC32;
+
+const CRC16_POLY: u16 = 0x1021;
+const CRC32_POLY: u32 = 0x04C1_1DB7;
+
+pub const CRC16_CCITT_SEED: u16 = 0xFFFF;
+pub const CRC32_ISO3309_SEED: u32 = 0xFFFF_FFFF;
+
+/// Common interface of the hardware and software CRC signature generators
+pub trait CrcEngine {
+    /// Signature size (`u16` for CRC16, `u32` for CRC32)
+    type Word: Copy;
+
+    /// Load the seed (initial signature)
+    fn set_seed(&mut self, seed: Self::Word);
+
+    /// Feed a byte, bit 0 first (CRCxDI)
+    fn feed_byte(&mut self, data: u8);
+
+    /// Feed a byte, bit 7 first (CRCxDIRB)
+    fn feed_byte_reversed(&mut self, data: u8);
+
+    /// Feed a 16-bit word, bit 0 first (CRCxDI)
+    fn feed_word(&mut self, data: u16);
+
+    /// Feed a 16-bit word, bit 15 first (CRCxDIRB)
+    fn feed_word_reversed(&mut self, data: u16);
+
+    /// Current signature (CRCxINIRES)
+    fn result(&self) -> Self::Word;
+
+    /// Current signature with its bits reversed (CRCxRESR)
+    fn result_reversed(&self) -> Self::Word;
+
+    /// Feed a byte slice, bit 0 of each byte first
+    fn feed_bytes(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.feed_byte(byte);
+        }
+    }
+
+    /// Feed a byte slice, bit 7 of each byte first
+    fn feed_bytes_reversed(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.feed_byte_reversed(byte);
+        }
+    }
+}
+
+pub trait CrcExt {
+    fn split(self) -> Parts;
+}
+
+impl CrcExt for CRC32 {
+    fn split(self) -> Parts {
+        Parts {
+            crc16: Crc16 { _crc: () },
+            crc32: Crc32 { _crc: () },
+        }
+    }
+}
+
+/// Independent CRC16 and CRC32 signature generators
+pub struct Parts {
+    pub crc16: Crc16,
+    pub crc32: Crc32,
+}
+
+/// CRC16 (CRC-CCITT) hardware signature generator
+pub struct Crc16 {
+    _crc: (),
+}
+
+/// CRC32 (ISO3309) hardware signature generator
+pub struct Crc32 {
+    _crc: (),
+}
+
+impl CrcEngine for Crc16 {
+    type Word = u16;
+
+    #[inline]
+    fn set_seed(&mut self, seed: u16) {
+        let crc = unsafe { &*CRC32::ptr() };
+        crc.crc16inires.write(|w| unsafe { w.bits(seed) });
+    }
+
+    #[inline]
+    fn feed_byte(&mut self, data: u8) {
+        let crc = unsafe { &*CRC32::ptr() };
+        let di = &crc.crc16di as *const _ as *mut u8;
+        unsafe { di.write_volatile(data) };
+    }
+
+    #[inline]
+    fn feed_byte_reversed(&mut self, data: u8) {
+        let crc = unsafe { &*CRC32::ptr() };
+        let dirb = &crc.crc16dirb as *const _ as *mut u8;
+        unsafe { dirb.write_volatile(data) };
+    }
+
+    #[inline]
+    fn feed_word(&mut self, data: u16) {
+        let crc = unsafe { &*CRC32::ptr() };
+        crc.crc16di.write(|w| unsafe { w.bits(data) });
+    }
+
+    #[inline]
+    fn feed_word_reversed(&mut self, data: u16) {
+        let crc = unsafe { &*CRC32::ptr() };
+        crc.crc16dirb.write(|w| unsafe { w.bits(data) });
+    }
+
+    #[inline]
+    fn result(&self) -> u16 {
+        let crc = unsafe { &*CRC32::ptr() };
+        crc.crc16inires.read().bits()
+    }
+
+    #[inline]
+    fn result_reversed(&self) -> u16 {
+        let crc = unsafe { &*CRC32::ptr() };
+        crc.crc16resr.read().bits()
+    }
+}
+
+impl CrcEngine for Crc32 {
+    type Word = u32;
+
+    #[inline]
+    fn set_seed(&mut self, seed: u32) {
+        let crc = unsafe { &*CRC32::ptr() };
+        crc.crc32inires_lo.write(|w| unsafe { w.bits(seed as u16) });
+        crc.crc32inires_hi.write(|w| unsafe { w.bits((seed >> 16) as u16) });
+    }
+
+    #[inline]
+    fn feed_byte(&mut self, data: u8) {
+        let crc = unsafe { &*CRC32::ptr() };
+        let di = &crc.crc32di as *const _ as *mut u8;
+        unsafe { di.write_volatile(data) };
+    }
+
+    #[inline]
+    fn feed_byte_reversed(&mut self, data: u8) {
+        let crc = unsafe { &*CRC32::ptr() };
+        let dirb = &crc.crc32dirb as *const _ as *mut u8;
+        unsafe { dirb.write_volatile(data) };
+    }
+
+    #[inline]
+    fn feed_word(&mut self, data: u16) {
+        let crc = unsafe { &*CRC32::ptr() };
+        crc.crc32di.write(|w| unsafe { w.bits(data) });
+    }
+
+    #[inline]
+    fn feed_word_reversed(&mut self, data: u16) {
+        let crc = unsafe { &*CRC32::ptr() };
+        crc.crc32dirb.write(|w| unsafe { w.bits(data) });
+    }
+
+    #[inline]
+    fn result(&self) -> u32 {
+        let crc = unsafe { &*CRC32::ptr() };
+        let lo = crc.crc32inires_lo.read().bits() as u32;
+        let hi = crc.crc32inires_hi.read().bits() as u32;
+        (hi << 16) | lo
+    }
+
+    #[inline]
+    fn result_reversed(&self) -> u32 {
+        let crc = unsafe { &*CRC32::ptr() };
+        let lo = crc.crc32resr_lo.read().bits() as u32;
+        let hi = crc.crc32resr_hi.read().bits() as u32;
+        (hi << 16) | lo
+    }
+}
+
+/// Software model of the CRC16 generator, for devices or contexts without the peripheral
+#[derive(Debug, Clone, Copy)]
+pub struct SoftCrc16 {
+    signature: u16,
+}
+
+impl SoftCrc16 {
+    pub const fn new() -> Self {
+        SoftCrc16 { signature: CRC16_CCITT_SEED }
+    }
+
+    #[inline]
+    fn shift_msb_first(&mut self, data: u16, bits: u32) {
+        for n in (0..bits).rev() {
+            let feedback = ((self.signature >> 15) ^ (data >> n)) & 0x01;
+            self.signature <<= 1;
+            if feedback != 0 {
+                self.signature ^= CRC16_POLY;
+            }
+        }
+    }
+}
+
+impl Default for SoftCrc16 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrcEngine for SoftCrc16 {
+    type Word = u16;
+
+    fn set_seed(&mut self, seed: u16) {
+        self.signature = seed;
+    }
+
+    fn feed_byte(&mut self, data: u8) {
+        self.shift_msb_first(data.reverse_bits() as u16, 8);
+    }
+
+    fn feed_byte_reversed(&mut self, data: u8) {
+        self.shift_msb_first(data as u16, 8);
+    }
+
+    fn feed_word(&mut self, data: u16) {
+        self.shift_msb_first(data.reverse_bits(), 16);
+    }
+
+    fn feed_word_reversed(&mut self, data: u16) {
+        self.shift_msb_first(data, 16);
+    }
+
+    fn result(&self) -> u16 {
+        self.signature
+    }
+
+    fn result_reversed(&self) -> u16 {
+        self.signature.reverse_bits()
+    }
+}
+
+/// Software model of the CRC32 generator, for devices or contexts without the peripheral
+#[derive(Debug, Clone, Copy)]
+pub struct SoftCrc32 {
+    signature: u32,
+}
+
+impl SoftCrc32 {
+    pub const fn new() -> Self {
+        SoftCrc32 { signature: CRC32_ISO3309_SEED }
+    }
+
+    #[inline]
+    fn shift_msb_first(&mut self, data: u16, bits: u32) {
+        for n in (0..bits).rev() {
+            let feedback = ((self.signature >> 31) ^ (data as u32 >> n)) & 0x01;
+            self.signature <<= 1;
+            if feedback != 0 {
+                self.signature ^= CRC32_POLY;
+            }
+        }
+    }
+}
+
+impl Default for SoftCrc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CrcEngine for SoftCrc32 {
+    type Word = u32;
+
+    fn set_seed(&mut self, seed: u32) {
+        self.signature = seed;
+    }
+
+    fn feed_byte(&mut self, data: u8) {
+        self.shift_msb_first(data.reverse_bits() as u16, 8);
+    }
+
+    fn feed_byte_reversed(&mut self, data: u8) {
+        self.shift_msb_first(data as u16, 8);
+    }
+
+    fn feed_word(&mut self, data: u16) {
+        self.shift_msb_first(data.reverse_bits(), 16);
+    }
+
+    fn feed_word_reversed(&mut self, data: u16) {
+        self.shift_msb_first(data, 16);
+    }
+
+    fn result(&self) -> u32 {
+        self.signature
+    }
+
+    fn result_reversed(&self) -> u32 {
+        self.signature.reverse_bits()
+    }
+}
+
+/// Incremental CRC-16/CCITT-FALSE (seed 0xFFFF, MSB first, no final XOR)
+pub struct Crc16Hasher<E: CrcEngine<Word = u16>> {
+    engine: E,
+}
+
+impl<E: CrcEngine<Word = u16>> Crc16Hasher<E> {
+    pub fn new(mut engine: E) -> Self {
+        engine.set_seed(CRC16_CCITT_SEED);
+        Crc16Hasher { engine }
+    }
+
+    #[inline]
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.engine.feed_bytes_reversed(data);
+        self
+    }
+
+    #[inline]
+    pub fn checksum(&self) -> u16 {
+        self.engine.result()
+    }
+
+    /// Restart the computation with the default seed
+    pub fn reset(&mut self) -> &mut Self {
+        self.engine.set_seed(CRC16_CCITT_SEED);
+        self
+    }
+
+    pub fn release(self) -> E {
+        self.engine
+    }
+}
+
+impl<E: CrcEngine<Word = u16>> Hasher for Crc16Hasher<E> {
+    fn finish(&self) -> u64 {
+        self.checksum() as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+}
+
+/// Incremental CRC-32/ISO-HDLC (seed 0xFFFFFFFF, LSB first, final XOR 0xFFFFFFFF)
+pub struct Crc32Hasher<E: CrcEngine<Word = u32>> {
+    engine: E,
+}
+
+impl<E: CrcEngine<Word = u32>> Crc32Hasher<E> {
+    pub fn new(mut engine: E) -> Self {
+        engine.set_seed(CRC32_ISO3309_SEED);
+        Crc32Hasher { engine }
+    }
+
+    #[inline]
+    pub fn update(&mut self, data: &[u8]) -> &mut Self {
+        self.engine.feed_bytes(data);
+        self
+    }
+
+    #[inline]
+    pub fn checksum(&self) -> u32 {
+        self.engine.result_reversed() ^ 0xFFFF_FFFF
+    }
+
+    /// Restart the computation with the default seed
+    pub fn reset(&mut self) -> &mut Self {
+        self.engine.set_seed(CRC32_ISO3309_SEED);
+        self
+    }
+
+    pub fn release(self) -> E {
+        self.engine
+    }
+}
+
+impl<E: CrcEngine<Word = u32>> Hasher for Crc32Hasher<E> {
+    fn finish(&self) -> u64 {
+        self.checksum() as u64
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.update(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECK: &[u8] = b"123456789";
+
+    /// Bitwise reflected CRC-32 (polynomial 0xEDB88320)
+    fn reference_crc32(data: &[u8]) -> u32 {
+        let mut crc = 0xFFFF_FFFF;
+
+        for &byte in data {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = if crc & 0x01 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            }
+        }
+
+        crc ^ 0xFFFF_FFFF
+    }
+
+    #[test]
+    fn crc16_check_value() {
+        let mut hasher = Crc16Hasher::new(SoftCrc16::new());
+        assert_eq!(hasher.update(CHECK).checksum(), 0x29B1);
+
+        hasher.reset().update(&CHECK[..4]).update(&CHECK[4..]);
+        assert_eq!(hasher.checksum(), 0x29B1);
+        assert_eq!(hasher.finish(), 0x29B1);
+    }
+
+    #[test]
+    fn crc32_check_value() {
+        let mut hasher = Crc32Hasher::new(SoftCrc32::new());
+        assert_eq!(hasher.update(CHECK).checksum(), 0xCBF4_3926);
+
+        hasher.reset().update(&CHECK[..5]).update(&CHECK[5..]);
+        assert_eq!(hasher.checksum(), 0xCBF4_3926);
+        assert_eq!(hasher.finish(), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn crc32_matches_reference() {
+        let data: [u8; 256] = core::array::from_fn(|n| (n as u8).wrapping_mul(37) ^ 0x5A);
+
+        for length in 0..data.len() {
+            let mut hasher = Crc32Hasher::new(SoftCrc32::new());
+            assert_eq!(hasher.update(&data[..length]).checksum(), reference_crc32(&data[..length]));
+        }
+    }
+
+    #[test]
+    fn word_feeds_match_byte_feeds() {
+        let mut words = SoftCrc16::new();
+        let mut bytes = SoftCrc16::new();
+
+        // CRCxDI: bit 0 first, so the low byte first
+        words.feed_word(0x3231);
+        bytes.feed_bytes(b"12");
+        assert_eq!(words.result(), bytes.result());
+
+        // CRCxDIRB: bit 15 first, so the high byte first
+        words.feed_word_reversed(0x3334);
+        bytes.feed_bytes_reversed(b"34");
+        assert_eq!(words.result(), bytes.result());
+
+        let mut words = SoftCrc32::new();
+        let mut bytes = SoftCrc32::new();
+
+        words.feed_word(0x3231);
+        words.feed_word_reversed(0x3334);
+        bytes.feed_bytes(b"12");
+        bytes.feed_bytes_reversed(b"34");
+        assert_eq!(words.result(), bytes.result());
+        assert_eq!(words.result_reversed(), bytes.result().reverse_bits());
+    }
+}
@@ -0,0 +1,959 @@
+//! HAL library for the Flash key-value store - MSP432P401R
+
+/*
+Small key-value store for configuration data, kept in a reserved range of (at least two) flash
+sectors. The store only relies on the `embedded-storage` `NorFlash` trait, so it runs on top of
+an `Unlocked` range of the main memory or of the information memory (`Unlocked::info_memory`, see
+`protect.rs`) as well as on any other flash or on a RAM model of one on the host.
+
+-- LAYOUT: --
+
+SECTOR --> Header (magic, generation, CRC32 of both) followed by the records;
+RECORD --> Key (u16), length (u16, bit 15 set for a removed key), CRC32 of the key, the length and
+           the value, then the value. Records are padded with 0xFF to the write size (at least 8
+           bytes);
+
+-- WEAR LEVELING: --
+
+Records are only appended: setting a key writes a new record and removing it writes a tombstone,
+the last record of a key wins. When the active sector is full, the next sector in the range is
+erased and becomes the active one (with the next generation). The sector after it, the oldest one,
+is then garbage collected: its records that are still the last ones of their key are copied to the
+active sector, and it's erased. Every sector of the range is erased in turn, and one sector is
+always kept free.
+
+-- POWER FAILURES: --
+
+A record is valid only if its CRC matches, a sector is valid only if its header CRC matches. An
+interrupted record closes its sector (the next write moves to a new sector), an interrupted garbage
+collection is resumed by `KvStore::new`.
+
+The CRC can be computed by the CRC32 module (`crc::Crc32`) or in software (`crc::SoftCrc32`).
+*/
+
+use embedded_storage::nor_flash::NorFlash;
+
+use crate::crc::{Crc32Hasher, CrcEngine};
+
+const SECTOR_MAGIC: u32 = 0x3153_564B;
+const SECTOR_HEADER_SIZE: u32 = 12;
+const RECORD_HEADER_SIZE: usize = 8;
+const BUFFER_SIZE: usize = 64;
+const MIN_ALIGN: usize = 8;
+
+const ERASED_KEY: u16 = 0xFFFF;
+const ERASED_WORD: u32 = 0xFFFF_FFFF;
+const TOMBSTONE: u16 = 0x8000;
+const LENGTH_MASK: u16 = 0x7FFF;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error<E> {
+    Flash(E),
+    /// Range not aligned to the erase size, shorter than two sectors or outside of the flash
+    Region,
+    /// 0xFFFF is reserved for erased records
+    InvalidKey,
+    /// The value doesn't fit in a sector
+    TooLarge,
+    /// The live records don't fit in the store
+    Full,
+    /// The buffer is smaller than the value
+    BufferTooSmall,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Record {
+    key: u16,
+    length: usize,
+    removed: bool,
+    address: u32,
+    size: u32,
+}
+
+enum Slot {
+    Record(Record),
+    Erased,
+    Corrupted,
+}
+
+/// Wear-leveled key-value store over `sectors` flash sectors starting at `start`
+pub struct KvStore<F: NorFlash, E: CrcEngine<Word = u32>> {
+    flash: F,
+    crc: Crc32Hasher<E>,
+    start: u32,
+    sectors: u32,
+    active: u32,
+    generation: u32,
+    offset: u32,
+}
+
+impl<F: NorFlash, E: CrcEngine<Word = u32>> KvStore<F, E> {
+    /// Mount the store, formatting the range if it holds no valid sector
+    pub fn new(flash: F, crc: E, start: u32, sectors: u32) -> Result<Self, Error<F::Error>> {
+        let align = Self::align();
+
+        if sectors < 2
+            || start % F::ERASE_SIZE as u32 != 0
+            || F::ERASE_SIZE % align != 0
+            || BUFFER_SIZE % align != 0
+            || start as usize + sectors as usize * F::ERASE_SIZE > flash.capacity()
+        {
+            return Err(Error::Region);
+        }
+
+        let mut store = KvStore {
+            flash,
+            crc: Crc32Hasher::new(crc),
+            start,
+            sectors,
+            active: 0,
+            generation: 0,
+            offset: 0,
+        };
+
+        let mut newest = None;
+
+        for sector in 0..sectors {
+            if let Some(generation) = store.sector_generation(sector)? {
+                if newest.map_or(true, |(_, newest)| generation > newest) {
+                    newest = Some((sector, generation));
+                }
+            }
+        }
+
+        match newest {
+            Some((sector, generation)) => {
+                store.active = sector;
+                store.generation = generation;
+                let end = store.end_of(sector)?;
+                store.offset = end.unwrap_or(F::ERASE_SIZE as u32);
+
+                let oldest = store.next(sector);
+
+                // Interrupted garbage collection, the active sector only holds copies of the
+                // records of the oldest one: start over if a copy was interrupted
+                if store.sector_generation(oldest)?.is_some() {
+                    if end.is_none() {
+                        store.erase(sector)?;
+                        store.open(sector, generation)?;
+                    }
+
+                    store.collect(oldest)?;
+                }
+            }
+            None => store.format()?,
+        }
+
+        Ok(store)
+    }
+
+    /// Erase every sector of the store
+    pub fn format(&mut self) -> Result<(), Error<F::Error>> {
+        for sector in 0..self.sectors {
+            self.erase(sector)?;
+        }
+
+        self.open(0, 0)
+    }
+
+    /// Copy the value of `key` to `buffer`, returning its length
+    pub fn get(&mut self, key: u16, buffer: &mut [u8]) -> Result<Option<usize>, Error<F::Error>> {
+        let record = match self.latest(key)? {
+            Some(record) if !record.removed => record,
+            _ => return Ok(None),
+        };
+
+        if buffer.len() < record.length {
+            return Err(Error::BufferTooSmall);
+        }
+
+        read_value(&mut self.flash, &record, |offset, data| {
+            buffer[offset..offset + data.len()].copy_from_slice(data);
+        })
+        .map_err(Error::Flash)?;
+
+        Ok(Some(record.length))
+    }
+
+    pub fn contains(&mut self, key: u16) -> Result<bool, Error<F::Error>> {
+        Ok(matches!(self.latest(key)?, Some(record) if !record.removed))
+    }
+
+    /// Store `value` under `key`, nothing is written if the value didn't change
+    pub fn set(&mut self, key: u16, value: &[u8]) -> Result<(), Error<F::Error>> {
+        if key == ERASED_KEY {
+            return Err(Error::InvalidKey);
+        }
+
+        if value.len() > LENGTH_MASK as usize
+            || Self::record_size(value.len()) > F::ERASE_SIZE as u32 - self.header_size()
+        {
+            return Err(Error::TooLarge);
+        }
+
+        if let Some(record) = self.latest(key)? {
+            if !record.removed && record.length == value.len() {
+                let mut same = true;
+
+                read_value(&mut self.flash, &record, |offset, data| {
+                    same &= value[offset..offset + data.len()] == *data;
+                })
+                .map_err(Error::Flash)?;
+
+                if same {
+                    return Ok(());
+                }
+            }
+        }
+
+        self.append(key, value.len() as u16, value)
+    }
+
+    /// Remove `key`, returning whether it was stored
+    pub fn remove(&mut self, key: u16) -> Result<bool, Error<F::Error>> {
+        if key == ERASED_KEY {
+            return Err(Error::InvalidKey);
+        }
+
+        match self.latest(key)? {
+            Some(record) if !record.removed => {
+                self.append(key, TOMBSTONE, &[])?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    pub fn release(self) -> (F, E) {
+        (self.flash, self.crc.release())
+    }
+
+    fn append(&mut self, key: u16, length: u16, value: &[u8]) -> Result<(), Error<F::Error>> {
+        let size = Self::record_size(value.len());
+
+        for _ in 0..self.sectors {
+            if self.offset + size <= F::ERASE_SIZE as u32 {
+                break;
+            }
+
+            self.advance()?;
+        }
+
+        if self.offset + size > F::ERASE_SIZE as u32 {
+            return Err(Error::Full);
+        }
+
+        let mut header = [0xFF; RECORD_HEADER_SIZE];
+        header[0..2].copy_from_slice(&key.to_le_bytes());
+        header[2..4].copy_from_slice(&length.to_le_bytes());
+
+        self.crc.reset().update(&header[0..4]).update(value);
+        header[4..8].copy_from_slice(&self.crc.checksum().to_le_bytes());
+
+        let address = self.sector_address(self.active) + self.offset;
+        let mut buffer = [0xFF; BUFFER_SIZE];
+        let mut position = 0;
+
+        while position < size as usize {
+            let chunk = (size as usize - position).min(BUFFER_SIZE);
+
+            for (index, byte) in buffer[..chunk].iter_mut().enumerate() {
+                let offset = position + index;
+
+                *byte = if offset < RECORD_HEADER_SIZE {
+                    header[offset]
+                } else {
+                    value
+                        .get(offset - RECORD_HEADER_SIZE)
+                        .copied()
+                        .unwrap_or(0xFF)
+                };
+            }
+
+            self.flash
+                .write(address + position as u32, &buffer[..chunk])
+                .map_err(Error::Flash)?;
+            position += chunk;
+        }
+
+        self.offset += size;
+        Ok(())
+    }
+
+    /// Move to the next sector and garbage collect the oldest one
+    fn advance(&mut self) -> Result<(), Error<F::Error>> {
+        let sector = self.next(self.active);
+
+        self.erase(sector)?;
+        self.open(sector, self.generation.wrapping_add(1))?;
+
+        let oldest = self.next(sector);
+
+        if self.sector_generation(oldest)?.is_some() {
+            self.collect(oldest)?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy the live records of `sector` to the active sector, then erase it
+    fn collect(&mut self, sector: u32) -> Result<(), Error<F::Error>> {
+        let mut offset = self.header_size();
+
+        while let Slot::Record(record) = self.record_at(sector, offset)? {
+            if !record.removed && self.latest(record.key)? == Some(record) {
+                if self.offset + record.size > F::ERASE_SIZE as u32 {
+                    return Err(Error::Full);
+                }
+
+                self.copy(&record)?;
+            }
+
+            offset += record.size;
+        }
+
+        self.erase(sector)
+    }
+
+    fn copy(&mut self, record: &Record) -> Result<(), Error<F::Error>> {
+        let address = self.sector_address(self.active) + self.offset;
+        let mut buffer = [0; BUFFER_SIZE];
+        let mut position = 0;
+
+        while position < record.size {
+            let chunk = ((record.size - position) as usize).min(BUFFER_SIZE);
+
+            self.flash
+                .read(record.address + position, &mut buffer[..chunk])
+                .map_err(Error::Flash)?;
+            self.flash
+                .write(address + position, &buffer[..chunk])
+                .map_err(Error::Flash)?;
+            position += chunk as u32;
+        }
+
+        self.offset += record.size;
+        Ok(())
+    }
+
+    /// Last record of `key`, from the oldest to the active sector
+    fn latest(&mut self, key: u16) -> Result<Option<Record>, Error<F::Error>> {
+        let mut latest = None;
+
+        for step in 1..=self.sectors {
+            let sector = (self.active + step) % self.sectors;
+
+            if self.sector_generation(sector)?.is_none() {
+                continue;
+            }
+
+            let mut offset = self.header_size();
+
+            while let Slot::Record(record) = self.record_at(sector, offset)? {
+                if record.key == key {
+                    latest = Some(record);
+                }
+
+                offset += record.size;
+            }
+        }
+
+        Ok(latest)
+    }
+
+    /// Write position after the last record of `sector`, `None` if a record was interrupted
+    fn end_of(&mut self, sector: u32) -> Result<Option<u32>, Error<F::Error>> {
+        let mut offset = self.header_size();
+
+        loop {
+            match self.record_at(sector, offset)? {
+                Slot::Record(record) => offset += record.size,
+                Slot::Erased => return Ok(Some(offset)),
+                Slot::Corrupted => return Ok(None),
+            }
+        }
+    }
+
+    fn record_at(&mut self, sector: u32, offset: u32) -> Result<Slot, Error<F::Error>> {
+        if offset + RECORD_HEADER_SIZE as u32 > F::ERASE_SIZE as u32 {
+            return Ok(Slot::Erased);
+        }
+
+        let address = self.sector_address(sector) + offset;
+        let mut header = [0; BUFFER_SIZE];
+
+        self.flash
+            .read(address, &mut header[..Self::align()])
+            .map_err(Error::Flash)?;
+
+        let key = u16::from_le_bytes([header[0], header[1]]);
+        let length = u16::from_le_bytes([header[2], header[3]]);
+        let checksum = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+
+        if key == ERASED_KEY && length == ERASED_KEY && checksum == ERASED_WORD {
+            return Ok(Slot::Erased);
+        }
+
+        let record = Record {
+            key,
+            length: (length & LENGTH_MASK) as usize,
+            removed: length & TOMBSTONE != 0,
+            address,
+            size: Self::record_size((length & LENGTH_MASK) as usize),
+        };
+
+        if key == ERASED_KEY || offset + record.size > F::ERASE_SIZE as u32 {
+            return Ok(Slot::Corrupted);
+        }
+
+        let crc = &mut self.crc;
+        crc.reset().update(&header[0..4]);
+        read_value(&mut self.flash, &record, |_, data| {
+            crc.update(data);
+        })
+        .map_err(Error::Flash)?;
+
+        if crc.checksum() == checksum {
+            Ok(Slot::Record(record))
+        } else {
+            Ok(Slot::Corrupted)
+        }
+    }
+
+    /// Generation of a valid (formatted) sector
+    fn sector_generation(&mut self, sector: u32) -> Result<Option<u32>, Error<F::Error>> {
+        let mut buffer = [0; BUFFER_SIZE];
+        let size = self.header_size() as usize;
+
+        self.flash
+            .read(self.sector_address(sector), &mut buffer[..size])
+            .map_err(Error::Flash)?;
+
+        let word = |index: usize| {
+            u32::from_le_bytes([
+                buffer[index],
+                buffer[index + 1],
+                buffer[index + 2],
+                buffer[index + 3],
+            ])
+        };
+
+        self.crc.reset().update(&buffer[0..8]);
+
+        if word(0) == SECTOR_MAGIC && word(8) == self.crc.checksum() {
+            Ok(Some(word(4)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Make the (erased) `sector` the active one
+    fn open(&mut self, sector: u32, generation: u32) -> Result<(), Error<F::Error>> {
+        let mut buffer = [0xFF; BUFFER_SIZE];
+        let size = self.header_size() as usize;
+
+        buffer[0..4].copy_from_slice(&SECTOR_MAGIC.to_le_bytes());
+        buffer[4..8].copy_from_slice(&generation.to_le_bytes());
+        self.crc.reset().update(&buffer[0..8]);
+        buffer[8..12].copy_from_slice(&self.crc.checksum().to_le_bytes());
+
+        self.flash
+            .write(self.sector_address(sector), &buffer[..size])
+            .map_err(Error::Flash)?;
+
+        self.active = sector;
+        self.generation = generation;
+        self.offset = size as u32;
+        Ok(())
+    }
+
+    fn erase(&mut self, sector: u32) -> Result<(), Error<F::Error>> {
+        let address = self.sector_address(sector);
+
+        self.flash
+            .erase(address, address + F::ERASE_SIZE as u32)
+            .map_err(Error::Flash)
+    }
+
+    #[inline]
+    fn next(&self, sector: u32) -> u32 {
+        (sector + 1) % self.sectors
+    }
+
+    #[inline]
+    fn sector_address(&self, sector: u32) -> u32 {
+        self.start + sector * F::ERASE_SIZE as u32
+    }
+
+    #[inline]
+    fn header_size(&self) -> u32 {
+        round_up(SECTOR_HEADER_SIZE as usize, Self::align()) as u32
+    }
+
+    #[inline]
+    fn record_size(length: usize) -> u32 {
+        round_up(RECORD_HEADER_SIZE + length, Self::align()) as u32
+    }
+
+    /// Record alignment, a multiple of both the read and the write sizes
+    #[inline]
+    fn align() -> usize {
+        F::WRITE_SIZE.max(F::READ_SIZE).max(MIN_ALIGN)
+    }
+}
+
+#[inline]
+fn round_up(value: usize, align: usize) -> usize {
+    value.div_ceil(align) * align
+}
+
+/// Read the value of `record` in chunks, `f` gets the offset in the value and the data
+fn read_value<F: NorFlash>(
+    flash: &mut F,
+    record: &Record,
+    mut f: impl FnMut(usize, &[u8]),
+) -> Result<(), F::Error> {
+    let mut buffer = [0; BUFFER_SIZE];
+    let end = RECORD_HEADER_SIZE + record.length;
+    let mut position = 0;
+
+    while position < end {
+        let chunk = (record.size as usize - position).min(BUFFER_SIZE);
+        flash.read(record.address + position as u32, &mut buffer[..chunk])?;
+
+        let from = position.max(RECORD_HEADER_SIZE);
+        let to = (position + chunk).min(end);
+
+        if from < to {
+            f(
+                from - RECORD_HEADER_SIZE,
+                &buffer[from - position..to - position],
+            );
+        }
+
+        position += chunk;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crc::SoftCrc32;
+    use crate::flash::{
+        Error as FlashError, FlashSectors, InfoMemory, INFO_BANK_SIZE, INFO_BASE, INFO_SIZE,
+        SECTOR_SIZE,
+    };
+    use embedded_storage::nor_flash::{ErrorType, NorFlashError, NorFlashErrorKind, ReadNorFlash};
+    use std::vec;
+    use std::vec::Vec;
+
+    const SECTOR: usize = 128;
+    const SECTORS: u32 = 3;
+    const START: u32 = SECTOR as u32;
+
+    #[derive(Debug, Copy, Clone, PartialEq)]
+    enum RamError {
+        OutOfBounds,
+        NotAligned,
+        PowerLoss,
+    }
+
+    impl NorFlashError for RamError {
+        fn kind(&self) -> NorFlashErrorKind {
+            match *self {
+                RamError::OutOfBounds => NorFlashErrorKind::OutOfBounds,
+                RamError::NotAligned => NorFlashErrorKind::NotAligned,
+                RamError::PowerLoss => NorFlashErrorKind::Other,
+            }
+        }
+    }
+
+    /// RAM model of a NOR flash: writes can only program erased bytes, erases set a sector to
+    /// 0xFF. With `power` set, the power is lost after that many byte writes and sector erases.
+    struct RamFlash {
+        data: Vec<u8>,
+        power: Option<usize>,
+        operations: usize,
+        erases: Vec<usize>,
+    }
+
+    impl RamFlash {
+        fn new() -> Self {
+            let sectors = START as usize / SECTOR + SECTORS as usize;
+
+            RamFlash {
+                data: vec![0xFF; sectors * SECTOR],
+                power: None,
+                operations: 0,
+                erases: vec![0; sectors],
+            }
+        }
+
+        fn operation(&mut self) -> Result<(), RamError> {
+            match self.power.as_mut() {
+                Some(0) => return Err(RamError::PowerLoss),
+                Some(left) => *left -= 1,
+                None => {}
+            }
+
+            self.operations += 1;
+            Ok(())
+        }
+
+        fn check(&self, offset: u32, length: usize, align: usize) -> Result<(), RamError> {
+            if offset as usize + length > self.data.len() {
+                Err(RamError::OutOfBounds)
+            } else if offset as usize % align != 0 || length % align != 0 {
+                Err(RamError::NotAligned)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl ErrorType for RamFlash {
+        type Error = RamError;
+    }
+
+    impl ReadNorFlash for RamFlash {
+        const READ_SIZE: usize = 1;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+            self.check(offset, bytes.len(), Self::READ_SIZE)?;
+
+            let offset = offset as usize;
+            bytes.copy_from_slice(&self.data[offset..offset + bytes.len()]);
+            Ok(())
+        }
+
+        fn capacity(&self) -> usize {
+            self.data.len()
+        }
+    }
+
+    impl NorFlash for RamFlash {
+        const WRITE_SIZE: usize = 16;
+        const ERASE_SIZE: usize = SECTOR;
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+            self.check(from, (to - from) as usize, Self::ERASE_SIZE)?;
+
+            for sector in from as usize / SECTOR..to as usize / SECTOR {
+                self.operation()?;
+                self.data[sector * SECTOR..(sector + 1) * SECTOR].fill(0xFF);
+                self.erases[sector] += 1;
+            }
+
+            Ok(())
+        }
+
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.check(offset, bytes.len(), Self::WRITE_SIZE)?;
+
+            for (index, &byte) in bytes.iter().enumerate() {
+                self.operation()?;
+
+                let target = &mut self.data[offset as usize + index];
+                assert!(*target == 0xFF || byte == 0xFF, "write to a programmed byte");
+                *target &= byte;
+            }
+
+            Ok(())
+        }
+    }
+
+    fn mount(flash: RamFlash) -> KvStore<RamFlash, SoftCrc32> {
+        KvStore::new(flash, SoftCrc32::new(), START, SECTORS).unwrap()
+    }
+
+    fn remount(store: KvStore<RamFlash, SoftCrc32>) -> KvStore<RamFlash, SoftCrc32> {
+        let (mut flash, _) = store.release();
+        flash.power = None;
+        mount(flash)
+    }
+
+    fn get(store: &mut KvStore<RamFlash, SoftCrc32>, key: u16) -> Option<Vec<u8>> {
+        let mut buffer = [0; SECTOR];
+        let length = store.get(key, &mut buffer).unwrap()?;
+        Some(buffer[..length].to_vec())
+    }
+
+    /// Value of the nth update, from 0 to 24 bytes
+    fn value(n: usize) -> Vec<u8> {
+        (0..n % 25).map(|index| (n * 7 + index) as u8).collect()
+    }
+
+    #[test]
+    fn set_get_remove() {
+        let mut store = mount(RamFlash::new());
+
+        assert_eq!(get(&mut store, 1), None);
+        store.set(1, b"first").unwrap();
+        store.set(2, b"").unwrap();
+        store.set(1, b"second").unwrap();
+
+        assert_eq!(get(&mut store, 1).as_deref(), Some(&b"second"[..]));
+        assert_eq!(get(&mut store, 2).as_deref(), Some(&b""[..]));
+        assert_eq!(store.get(1, &mut [0; 2]), Err(Error::BufferTooSmall));
+
+        assert_eq!(store.remove(2), Ok(true));
+        assert_eq!(store.remove(2), Ok(false));
+        assert_eq!(store.contains(2), Ok(false));
+        assert_eq!(store.set(ERASED_KEY, b""), Err(Error::InvalidKey));
+        assert_eq!(store.set(3, &[0; SECTOR]), Err(Error::TooLarge));
+
+        let mut store = remount(store);
+        assert_eq!(get(&mut store, 1).as_deref(), Some(&b"second"[..]));
+        assert_eq!(get(&mut store, 2), None);
+
+        // Same value, nothing written
+        let operations = store.flash.operations;
+        store.set(1, b"second").unwrap();
+        assert_eq!(store.flash.operations, operations);
+    }
+
+    #[test]
+    fn compaction() {
+        let mut store = mount(RamFlash::new());
+        store.set(100, b"kept").unwrap();
+
+        for n in 0..200 {
+            store.set((n % 4) as u16, &value(n)).unwrap();
+        }
+
+        for n in 196..200 {
+            assert_eq!(get(&mut store, (n % 4) as u16), Some(value(n)));
+        }
+        assert_eq!(get(&mut store, 100).as_deref(), Some(&b"kept"[..]));
+
+        // Every sector of the range was erased in turn, none outside of it
+        let erases = &store.flash.erases;
+        assert_eq!(erases[0], 0);
+        assert!(erases[1..].iter().all(|&count| count > 1));
+        assert!(erases.iter().max().unwrap() - erases[1..].iter().min().unwrap() <= 1);
+
+        let mut store = remount(store);
+        for n in 196..200 {
+            assert_eq!(get(&mut store, (n % 4) as u16), Some(value(n)));
+        }
+        assert_eq!(get(&mut store, 100).as_deref(), Some(&b"kept"[..]));
+    }
+
+    /// RAM model of the information memory, by flash address
+    struct RamInfoMemory {
+        data: Vec<u8>,
+        erases: Vec<u32>,
+    }
+
+    impl RamInfoMemory {
+        fn range(&self, address: u32, length: usize) -> Result<usize, FlashError> {
+            let offset = address.checked_sub(INFO_BASE).ok_or(FlashError::OutOfBounds)? as usize;
+
+            if offset + length > self.data.len() {
+                return Err(FlashError::OutOfBounds);
+            }
+            Ok(offset)
+        }
+    }
+
+    impl FlashSectors for RamInfoMemory {
+        fn read(&mut self, address: u32, bytes: &mut [u8]) -> Result<(), FlashError> {
+            let offset = self.range(address, bytes.len())?;
+            bytes.copy_from_slice(&self.data[offset..offset + bytes.len()]);
+            Ok(())
+        }
+
+        fn program(&mut self, address: u32, bytes: &[u8]) -> Result<(), FlashError> {
+            let offset = self.range(address, bytes.len())?;
+            for (target, &byte) in self.data[offset..].iter_mut().zip(bytes.iter()) {
+                assert!(*target == 0xFF || byte == 0xFF, "write to a programmed byte");
+                *target &= byte;
+            }
+            Ok(())
+        }
+
+        fn erase_sector(&mut self, address: u32) -> Result<(), FlashError> {
+            let offset = self.range(address, 1)?;
+            let start = offset - offset % SECTOR_SIZE as usize;
+            self.data[start..start + SECTOR_SIZE as usize].fill(0xFF);
+            self.erases.push(address);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn information_memory() {
+        let mut flash = RamInfoMemory {
+            data: vec![0xFF; INFO_SIZE as usize],
+            erases: Vec::new(),
+        };
+        // Bank 1 of the information memory
+        let start = INFO_BANK_SIZE;
+
+        let info = InfoMemory::new(&mut flash);
+        assert_eq!(
+            KvStore::new(info, SoftCrc32::new(), start + SECTOR_SIZE, 2).err(),
+            Some(Error::Region)
+        );
+
+        let info = InfoMemory::new(&mut flash);
+        let mut store = KvStore::new(info, SoftCrc32::new(), start, 2).unwrap();
+        store.set(7, b"calibration").unwrap();
+        let mut buffer = [0; 16];
+        assert_eq!(store.get(7, &mut buffer), Ok(Some(11)));
+        store.release();
+
+        // The sectors of bank 1 hold the store, the mailbox and the TLV sectors are untouched
+        assert_eq!(flash.erases, [0x0020_2000, 0x0020_3000]);
+        assert!(flash.data[..INFO_BANK_SIZE as usize].iter().all(|&byte| byte == 0xFF));
+        let magic = &flash.data[0x2000..0x2004];
+        assert_eq!(u32::from_le_bytes([magic[0], magic[1], magic[2], magic[3]]), SECTOR_MAGIC);
+
+        let info = InfoMemory::new(&mut flash);
+        let mut store = KvStore::new(info, SoftCrc32::new(), start, 2).unwrap();
+        assert_eq!(store.get(7, &mut buffer), Ok(Some(11)));
+        assert_eq!(&buffer[..11], b"calibration");
+
+        // Out of the information memory
+        let mut info = InfoMemory::new(&mut flash);
+        assert_eq!(info.write(INFO_SIZE - 16, &[0; 32]), Err(FlashError::OutOfBounds));
+        assert_eq!(info.erase(0x3000, 0x5000), Err(FlashError::OutOfBounds));
+        assert_eq!(info.erase(0x3000, 0x3800), Err(FlashError::NotAligned));
+    }
+
+    const KEYS: usize = 4;
+    const STEPS: usize = 40;
+
+    /// Update the keys in turn (every 7th update removes the key), `committed` holds the values
+    /// stored so far and `pending` the update in progress
+    fn updates(
+        store: &mut KvStore<RamFlash, SoftCrc32>,
+        committed: &mut [Option<Vec<u8>>; KEYS],
+        pending: &mut Option<(usize, Option<Vec<u8>>)>,
+    ) -> Result<(), Error<RamError>> {
+        for step in 0..STEPS {
+            let key = step % KEYS;
+            let update = if step % 7 == 6 { None } else { Some(value(step)) };
+            *pending = Some((key, update.clone()));
+
+            match &update {
+                Some(value) => store.set(key as u16, value)?,
+                None => store.remove(key as u16).map(|_| ())?,
+            }
+
+            committed[key] = update;
+        }
+
+        *pending = None;
+        Ok(())
+    }
+
+    fn prefilled() -> RamFlash {
+        let mut store = mount(RamFlash::new());
+
+        for key in 0..KEYS {
+            store.set(key as u16, &value(100 + key)).unwrap();
+        }
+
+        store.release().0
+    }
+
+    #[test]
+    fn power_loss_recovery() {
+        let initial: [Option<Vec<u8>>; KEYS] = core::array::from_fn(|key| Some(value(100 + key)));
+
+        let mut store = mount(prefilled());
+        let start = store.flash.operations;
+        updates(&mut store, &mut initial.clone(), &mut None).unwrap();
+        let total = store.flash.operations - start;
+
+        for budget in 0..total {
+            let mut flash = prefilled();
+            flash.power = Some(budget);
+
+            let mut store = mount(flash);
+            let mut committed = initial.clone();
+            let mut pending = None;
+
+            assert_eq!(
+                updates(&mut store, &mut committed, &mut pending),
+                Err(Error::Flash(RamError::PowerLoss))
+            );
+
+            let mut store = remount(store);
+            let (pending_key, pending_value) = pending.unwrap();
+
+            for (key, committed) in committed.iter().enumerate() {
+                let stored = get(&mut store, key as u16);
+
+                if key == pending_key {
+                    assert!(stored == *committed || stored == pending_value, "{}", budget);
+                } else {
+                    assert_eq!(stored, *committed, "{}", budget);
+                }
+            }
+
+            // The store keeps working
+            for key in 0..KEYS {
+                store.set(key as u16, &value(200 + key)).unwrap();
+            }
+
+            let mut store = remount(store);
+            for key in 0..KEYS {
+                assert_eq!(get(&mut store, key as u16), Some(value(200 + key)));
+            }
+        }
+    }
+
+    #[test]
+    fn torn_record() {
+        let mut store = mount(RamFlash::new());
+        store.set(1, b"old value").unwrap();
+
+        let address = (START + store.offset) as usize;
+        store.set(1, b"new value").unwrap();
+
+        // Value half programmed
+        store.flash.data[address + RECORD_HEADER_SIZE + 4..address + 32].fill(0xFF);
+
+        let mut store = remount(store);
+        assert_eq!(get(&mut store, 1).as_deref(), Some(&b"old value"[..]));
+
+        // The next record goes to a new sector
+        store.set(2, b"next").unwrap();
+        assert_eq!(store.active, 1);
+
+        let mut store = remount(store);
+        assert_eq!(get(&mut store, 1).as_deref(), Some(&b"old value"[..]));
+        assert_eq!(get(&mut store, 2).as_deref(), Some(&b"next"[..]));
+    }
+
+    #[test]
+    fn full_store() {
+        let mut store = mount(RamFlash::new());
+        let mut keys = 0;
+
+        loop {
+            match store.set(keys, &[keys as u8; 24]) {
+                Ok(()) => keys += 1,
+                Err(Error::Full) => break,
+                Err(error) => panic!("{:?}", error),
+            }
+        }
+
+        // Two of the three sectors hold live records
+        assert!(keys >= 4);
+
+        let mut store = remount(store);
+        for key in 0..keys {
+            assert_eq!(get(&mut store, key), Some(vec![key as u8; 24]));
+        }
+        assert_eq!(store.set(keys, &[0; 24]), Err(Error::Full));
+
+        // Unchanged values need no room
+        assert_eq!(store.set(0, &[0; 24]), Ok(()));
+        assert_eq!(get(&mut store, keys), None);
+    }
+}
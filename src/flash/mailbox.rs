@@ -0,0 +1,330 @@
+//! HAL library for the Flash mailbox (IP protection commands) - MSP432P401R
+
+/*
+The boot code processes the commands left in the flash mailbox, the first sector of the information
+memory (0x0020_0000), on the next reset. The mailbox is only processed when it starts with the
+MB_START word and ends with the MB_END word; the boot code then writes an acknowledgment for each
+command it applied and the resulting state is reported by `sysctl::SystemControl::security_status`.
+
+-- LAYOUT: --
+
+Flash mailbox structure of the MSP432P4xx technical reference manual (SLAU356, boot code chapter,
+"Flash Mailbox" and "Device Security" sections), offsets from 0x0020_0000:
+
+0x000 --> MB_START (start of mailbox word, 0x0115_ACF6);
+0x004 --> Command, one bit per secure zone (more bits for the JTAG/SWD lock, the BSL configuration
+          and the factory reset, not handled here);
+0x060 --> Secure zone 0 parameters, then zones 1 to 3 (0x58 bytes each): enable, start address,
+          length, AES initialization vector and keys (encrypted updates), unencrypted password,
+          encrypted update enable, data access enable, acknowledgment;
+0x1FC --> MB_END (end of mailbox word, 0x0011_E11D);
+
+These offsets and command bits could not be checked against the manual while writing this module:
+compare `Mailbox::to_bytes` with the mailbox of the TI security examples before writing it to a
+device, a wrong layout can permanently lock it.
+
+-- SECURE ZONES: --
+
+An IP protected secure zone is a range of main memory sectors that can only be read by the code of
+the zone itself (and, with `data_access`, read as data from outside of it); the debugger can't
+access it. The zone sectors are write/erase protected, an update needs the unencrypted password.
+Encrypted updates are not enabled by `Mailbox::secure_zone`: the AES vector and keys are left
+erased.
+
+`FlashControl::write_mailbox` erases the mailbox sector (an `Unlocked` range while it programs it)
+and programs the commands. A secure zone can't be removed but by a factory reset, check the zone
+layout twice before resetting the device.
+*/
+
+use super::{Defined, Error, FlashControl, INFO_BASE, MAIN_BASE, MAIN_SIZE, SECTOR_SIZE};
+
+pub const MAILBOX_ADDRESS: u32 = INFO_BASE;
+/// Size in bytes of the mailbox
+pub const MAILBOX_SIZE: usize = 0x200;
+
+const MB_START: u32 = 0x0115_ACF6;
+const MB_END: u32 = 0x0011_E11D;
+
+const START_OFFSET: usize = 0x000;
+const COMMAND_OFFSET: usize = 0x004;
+const END_OFFSET: usize = 0x1FC;
+
+const ZONE_OFFSET: usize = 0x060;
+const ZONE_SIZE: usize = 0x058;
+const ZONE_ENABLE: usize = 0x00;
+const ZONE_START: usize = 0x04;
+const ZONE_LENGTH: usize = 0x08;
+const ZONE_PASSWORD: usize = 0x3C;
+const ZONE_ENCRYPTED_UPDATE: usize = 0x4C;
+const ZONE_DATA_ACCESS: usize = 0x50;
+const ZONE_ACK: usize = 0x54;
+
+const COMMAND_SECURE_ZONE: u32 = 0x0000_0001;
+const ENABLED: u32 = 0x0000_0001;
+const DISABLED: u32 = 0x0000_0000;
+const ERASED_WORD: u32 = 0xFFFF_FFFF;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Zone {
+    Zone0 = 0,
+    Zone1 = 1,
+    Zone2 = 2,
+    Zone3 = 3,
+}
+
+impl Zone {
+    #[inline]
+    fn offset(self) -> usize {
+        ZONE_OFFSET + self as usize * ZONE_SIZE
+    }
+}
+
+/// IP protected secure zone
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SecureZone {
+    pub zone: Zone,
+    /// First address of the zone, sector aligned in the main memory
+    pub start: u32,
+    /// Length in bytes, a multiple of the sector size
+    pub length: u32,
+    /// Unencrypted password, needed to update the zone
+    pub password: [u32; 4],
+    /// Let the code outside of the zone read its data
+    pub data_access: bool,
+}
+
+/// Commands to leave in the flash mailbox
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mailbox {
+    words: [u32; MAILBOX_SIZE / 4],
+}
+
+impl Mailbox {
+    /// Mailbox without any command
+    pub fn new() -> Self {
+        let mut mailbox = Mailbox {
+            words: [ERASED_WORD; MAILBOX_SIZE / 4],
+        };
+
+        mailbox.set(START_OFFSET, MB_START);
+        mailbox.set(COMMAND_OFFSET, 0);
+        mailbox.set(END_OFFSET, MB_END);
+        mailbox
+    }
+
+    /// Set up `zone` on the next reset
+    pub fn secure_zone(mut self, zone: SecureZone) -> Result<Self, Error> {
+        let end = zone
+            .start
+            .checked_add(zone.length)
+            .ok_or(Error::OutOfBounds)?;
+
+        if zone.length == 0 || end > MAIN_BASE + MAIN_SIZE {
+            return Err(Error::OutOfBounds);
+        }
+
+        if zone.start % SECTOR_SIZE != 0 || zone.length % SECTOR_SIZE != 0 {
+            return Err(Error::NotAligned);
+        }
+
+        let offset = zone.zone.offset();
+        let command = self.get(COMMAND_OFFSET) | (COMMAND_SECURE_ZONE << zone.zone as u32);
+
+        self.set(COMMAND_OFFSET, command);
+        self.set(offset + ZONE_ENABLE, ENABLED);
+        self.set(offset + ZONE_START, zone.start);
+        self.set(offset + ZONE_LENGTH, zone.length);
+
+        for (index, &word) in zone.password.iter().enumerate() {
+            self.set(offset + ZONE_PASSWORD + index * 4, word);
+        }
+
+        self.set(offset + ZONE_ENCRYPTED_UPDATE, DISABLED);
+        self.set(
+            offset + ZONE_DATA_ACCESS,
+            if zone.data_access { ENABLED } else { DISABLED },
+        );
+        Ok(self)
+    }
+
+    /// Mailbox image, as programmed in the flash
+    pub fn to_bytes(&self) -> [u8; MAILBOX_SIZE] {
+        let mut bytes = [0; MAILBOX_SIZE];
+
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip(self.words.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    #[inline]
+    fn get(&self, offset: usize) -> u32 {
+        self.words[offset / 4]
+    }
+
+    #[inline]
+    fn set(&mut self, offset: usize, word: u32) {
+        self.words[offset / 4] = word;
+    }
+}
+
+impl Default for Mailbox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FlashControl<Defined> {
+    /// Erase the mailbox and program the commands of `mailbox`, applied on the next reset
+    pub fn write_mailbox(&mut self, mailbox: &Mailbox) -> Result<(), Error> {
+        let mut flash = self.unlock(MAILBOX_ADDRESS, SECTOR_SIZE)?;
+
+        flash.erase_sector(MAILBOX_ADDRESS)?;
+        flash.program_burst(MAILBOX_ADDRESS, &mailbox.to_bytes())
+    }
+
+    /// Acknowledgment written by the boot code for `zone`, `None` if it didn't process the command
+    pub fn secure_zone_ack(&self, zone: Zone) -> Result<Option<u32>, Error> {
+        let mut ack = [0; 4];
+        self.read(
+            MAILBOX_ADDRESS + (zone.offset() + ZONE_ACK) as u32,
+            &mut ack,
+        )?;
+
+        match u32::from_le_bytes(ack) {
+            ERASED_WORD => Ok(None),
+            ack => Ok(Some(ack)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ])
+    }
+
+    #[test]
+    fn mailbox_image() {
+        let zone = SecureZone {
+            zone: Zone::Zone1,
+            start: 0x0002_0000,
+            length: 0x0000_4000,
+            password: [0x0102_0304, 0x0506_0708, 0x090A_0B0C, 0x0D0E_0F10],
+            data_access: true,
+        };
+        let bytes = Mailbox::new().secure_zone(zone).unwrap().to_bytes();
+
+        // Expected words (offset, value), everything else erased
+        let words = [
+            (0x000, 0x0115_ACF6),
+            (0x004, 0x0000_0002),
+            (0x0B8, 0x0000_0001),
+            (0x0BC, 0x0002_0000),
+            (0x0C0, 0x0000_4000),
+            (0x0F4, 0x0102_0304),
+            (0x0F8, 0x0506_0708),
+            (0x0FC, 0x090A_0B0C),
+            (0x100, 0x0D0E_0F10),
+            (0x104, 0x0000_0000),
+            (0x108, 0x0000_0001),
+            (0x1FC, 0x0011_E11D),
+        ];
+        let mut expected = [0xFFFF_FFFFu32; MAILBOX_SIZE / 4];
+        for &(offset, value) in words.iter() {
+            expected[offset / 4] = value;
+        }
+
+        for (index, &expected) in expected.iter().enumerate() {
+            assert_eq!(word(&bytes, 4 * index), expected, "word at 0x{:03X}", 4 * index);
+        }
+    }
+
+    #[test]
+    fn empty_mailbox() {
+        let bytes = Mailbox::new().to_bytes();
+
+        assert_eq!(word(&bytes, START_OFFSET), MB_START);
+        assert_eq!(word(&bytes, COMMAND_OFFSET), 0);
+        assert_eq!(word(&bytes, END_OFFSET), MB_END);
+        assert!(bytes[COMMAND_OFFSET + 4..END_OFFSET]
+            .iter()
+            .all(|&byte| byte == 0xFF));
+    }
+
+    #[test]
+    fn secure_zones() {
+        let zone = SecureZone {
+            zone: Zone::Zone2,
+            start: 0x0001_0000,
+            length: 2 * SECTOR_SIZE,
+            password: [1, 2, 3, 4],
+            data_access: true,
+        };
+
+        let mailbox = Mailbox::new()
+            .secure_zone(zone)
+            .unwrap()
+            .secure_zone(SecureZone {
+                zone: Zone::Zone0,
+                data_access: false,
+                ..zone
+            })
+            .unwrap();
+        let bytes = mailbox.to_bytes();
+        let offset = ZONE_OFFSET + 2 * ZONE_SIZE;
+
+        assert_eq!(word(&bytes, COMMAND_OFFSET), 0x05);
+        assert_eq!(word(&bytes, offset + ZONE_ENABLE), ENABLED);
+        assert_eq!(word(&bytes, offset + ZONE_START), 0x0001_0000);
+        assert_eq!(word(&bytes, offset + ZONE_LENGTH), 0x2000);
+        assert_eq!(word(&bytes, offset + ZONE_PASSWORD + 12), 4);
+        assert_eq!(word(&bytes, offset + ZONE_ENCRYPTED_UPDATE), DISABLED);
+        assert_eq!(word(&bytes, offset + ZONE_DATA_ACCESS), ENABLED);
+        assert_eq!(word(&bytes, offset + ZONE_ACK), ERASED_WORD);
+        assert_eq!(word(&bytes, ZONE_OFFSET + ZONE_DATA_ACCESS), DISABLED);
+        assert_eq!(
+            word(&bytes, ZONE_OFFSET + ZONE_SIZE + ZONE_ENABLE),
+            ERASED_WORD
+        );
+    }
+
+    #[test]
+    fn invalid_zones() {
+        let zone = SecureZone {
+            zone: Zone::Zone0,
+            start: 0x0003_F000,
+            length: SECTOR_SIZE,
+            password: [0; 4],
+            data_access: false,
+        };
+
+        assert!(Mailbox::new().secure_zone(zone).is_ok());
+        assert_eq!(
+            Mailbox::new().secure_zone(SecureZone {
+                length: 2 * SECTOR_SIZE,
+                ..zone
+            }),
+            Err(Error::OutOfBounds)
+        );
+        assert_eq!(
+            Mailbox::new().secure_zone(SecureZone { length: 0, ..zone }),
+            Err(Error::OutOfBounds)
+        );
+        assert_eq!(
+            Mailbox::new().secure_zone(SecureZone {
+                start: 0x0003_E010,
+                ..zone
+            }),
+            Err(Error::NotAligned)
+        );
+    }
+}
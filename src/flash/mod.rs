@@ -0,0 +1,182 @@
+//! HAL library for Flash Control (FLCTL) - MSP432P401R
+pub mod kv;
+pub mod mailbox;
+pub mod program;
+pub mod protect;
+mod ram;
+pub mod read;
+pub mod update;
+
+pub use program::*;
+pub use protect::*;
+pub use read::*;
+
+use pac::FLCTL;
+
+use crate::clock::Clocks;
+use crate::pcm::VCoreSel;
+
+/// Highest MCLK for 0, 1 and 2 wait states
+const VCORE0_WAIT_LIMITS: [u32; 2] = [12_000_000, 24_000_000];
+const VCORE1_WAIT_LIMITS: [u32; 3] = [16_000_000, 32_000_000, 48_000_000];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WaitStatesError {
+    /// MCLK is too fast for the core voltage
+    ClockFrequency,
+}
+
+pub struct NotDefined;
+pub struct Defined;
+
+pub trait FlashExt {
+    fn constrain(self) -> FlashControl<NotDefined>;
+}
+
+impl FlashExt for FLCTL {
+    fn constrain(self) -> FlashControl<NotDefined> {
+        FlashControl::<NotDefined>::new(self)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FlashWaitStates {
+    _0 = 0x00,
+    _1 = 0x01,
+    _2 = 0x02,
+    _3 = 0x03,
+    _4 = 0x04,
+    _5 = 0x05,
+    _6 = 0x06,
+    _7 = 0x07,
+    _8 = 0x08,
+    _9 = 0x09,
+    _10 = 0x0A,
+    _11 = 0x0B,
+    _12 = 0x0C,
+    _13 = 0x0D,
+    _14 = 0x0E,
+    _15 = 0x0F,
+}
+
+impl FlashWaitStates {
+    /// Minimum wait states for an MCLK of `mclk` Hz in the `mode` active mode, `None` if the core
+    /// voltage can't run it (usable in a `const` to check the configuration at compile time)
+    pub const fn minimum(mclk: u32, mode: VCoreSel) -> Option<FlashWaitStates> {
+        if mclk > mode.max_frequencies().0 {
+            return None;
+        }
+
+        let limits: &[u32] = if mode.is_vcore1() {
+            &VCORE1_WAIT_LIMITS
+        } else {
+            &VCORE0_WAIT_LIMITS
+        };
+
+        let mut wait = 0;
+        while wait < limits.len() && mclk > limits[wait] {
+            wait += 1;
+        }
+
+        match wait {
+            0 => Some(FlashWaitStates::_0),
+            1 => Some(FlashWaitStates::_1),
+            2 => Some(FlashWaitStates::_2),
+            _ => None,
+        }
+    }
+}
+
+pub struct FlashControl<State> {
+    flash: FLCTL,
+    wait_states: FlashWaitStates,
+    _state: State,
+}
+
+impl FlashControl<NotDefined> {
+    fn new(flash: FLCTL) -> Self {
+        FlashControl::<NotDefined>{
+            flash,
+            wait_states: FlashWaitStates::_0,
+            _state: NotDefined,
+        }
+    }
+}
+
+impl FlashControl<Defined> {
+    pub fn freeze(self) -> Self {
+        self.set_read_control((self.wait_states as u32) << 12);
+        self
+    }
+
+    fn set_read_control(&self, value: u32) {
+        const MASK: u32 = 0xFFFF_0FFF;
+        self.flash.flctl_bank0_rdctl.modify(|r, w| unsafe {
+            w.bits((r.bits() & MASK) | value)
+        });
+
+        self.flash.flctl_bank1_rdctl.modify(|r, w| unsafe {
+            w.bits((r.bits() & MASK) | value)
+        });
+    }
+}
+
+impl<State> FlashControl<State>{
+    #[inline]
+    /// Set Wait States for the flash memory read operations (depends on the current clock speed)
+    pub fn set_waitstates(self, wait: FlashWaitStates) -> FlashControl<Defined> {
+        FlashControl::<Defined> {
+            flash: self.flash,
+            wait_states: wait,
+            _state: Defined,
+        }
+    }
+
+    /// Set the minimum wait states for the MCLK of `clocks` in the `mode` active mode
+    pub fn set_waitstates_for(
+        self,
+        clocks: &Clocks,
+        mode: VCoreSel,
+    ) -> Result<FlashControl<Defined>, WaitStatesError> {
+        let wait = FlashWaitStates::minimum(clocks.mclk.0, mode)
+            .ok_or(WaitStatesError::ClockFrequency)?;
+        Ok(self.set_waitstates(wait))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vcore0_wait_states() {
+        for mode in [VCoreSel::LdoVcore0, VCoreSel::DcdcVcore0] {
+            assert_eq!(FlashWaitStates::minimum(0, mode), Some(FlashWaitStates::_0));
+            assert_eq!(FlashWaitStates::minimum(12_000_000, mode), Some(FlashWaitStates::_0));
+            assert_eq!(FlashWaitStates::minimum(12_000_001, mode), Some(FlashWaitStates::_1));
+            assert_eq!(FlashWaitStates::minimum(24_000_000, mode), Some(FlashWaitStates::_1));
+            assert_eq!(FlashWaitStates::minimum(24_000_001, mode), None);
+        }
+    }
+
+    #[test]
+    fn vcore1_wait_states() {
+        for mode in [VCoreSel::LdoVcore1, VCoreSel::DcdcVcore1] {
+            assert_eq!(FlashWaitStates::minimum(0, mode), Some(FlashWaitStates::_0));
+            assert_eq!(FlashWaitStates::minimum(16_000_000, mode), Some(FlashWaitStates::_0));
+            assert_eq!(FlashWaitStates::minimum(16_000_001, mode), Some(FlashWaitStates::_1));
+            assert_eq!(FlashWaitStates::minimum(32_000_000, mode), Some(FlashWaitStates::_1));
+            assert_eq!(FlashWaitStates::minimum(32_000_001, mode), Some(FlashWaitStates::_2));
+            assert_eq!(FlashWaitStates::minimum(48_000_000, mode), Some(FlashWaitStates::_2));
+            assert_eq!(FlashWaitStates::minimum(48_000_001, mode), None);
+        }
+    }
+
+    #[test]
+    fn low_frequency_wait_states() {
+        for mode in [VCoreSel::LfVcore0, VCoreSel::LfVcore1] {
+            assert_eq!(FlashWaitStates::minimum(128_000, mode), Some(FlashWaitStates::_0));
+            assert_eq!(FlashWaitStates::minimum(128_001, mode), None);
+        }
+    }
+}
@@ -0,0 +1,470 @@
+//! HAL library for Flash programming and erasing - MSP432P401R
+
+/*
+-- MEMORY MAP: --
+
+MAIN MEMORY --> 0x0000_0000 - 0x0003_FFFF, bank 0 (0x0000_0000) and bank 1 (0x0002_0000),
+                32 sectors of 4 KB per bank;
+INFORMATION MEMORY --> 0x0020_0000 - 0x0020_3FFF, bank 0 (0x0020_0000) and bank 1 (0x0020_2000),
+                       2 sectors of 4 KB per bank (bank 0 holds the TLV and the BSL);
+
+-- PROGRAM MODES: --
+
+IMMEDIATE --> Each write (up to 32 bits) to the flash is programmed right away;
+FULL WORD --> Writes are collected until a full 128-bit flash word is composed, then programmed;
+BURST --> Up to four 128-bit words are loaded in FLCTL_PRGBRST_DATAx_y and programmed at once;
+
+The controller can verify a program operation before (the location is erased) and after (the
+location holds the data) programming it, see `set_program_verify`.
+
+-- PROTECTION: --
+
+Program and erase operations on a protected sector fail with `Error::Protected`. They are only
+available on an `Unlocked` range of sectors, see `protect.rs`.
+
+A bank can't be read while it's being programmed or erased, the operations are started and polled
+from SRAM (see `ram.rs`), so any sector of either bank can be programmed or erased.
+*/
+
+use core::ptr;
+
+use embedded_storage::nor_flash::{ErrorType, NorFlashError, NorFlashErrorKind, ReadNorFlash};
+use pac::FLCTL;
+
+use super::{ram, Defined, FlashControl};
+
+pub const MAIN_BASE: u32 = 0x0000_0000;
+pub const MAIN_SIZE: u32 = 0x0004_0000;
+pub const MAIN_BANK_SIZE: u32 = 0x0002_0000;
+pub const INFO_BASE: u32 = 0x0020_0000;
+pub const INFO_SIZE: u32 = 0x0000_4000;
+pub const INFO_BANK_SIZE: u32 = 0x0000_2000;
+pub const SECTOR_SIZE: u32 = 0x0000_1000;
+/// Size in bytes of a flash word
+pub const FLASH_WORD_SIZE: u32 = 16;
+
+const BURST_MAX_WORDS: u32 = 4;
+const BURST_DATA_OFFSET: usize = 0x60;
+const OPERATION_TIMEOUT: u32 = 1_000_000;
+
+const PRG_ENABLE: u32 = 0x01;
+const PRG_FULL_WORD: u32 = 0x02;
+const PRG_VERIFY_PRE: u32 = 0x04;
+const PRG_VERIFY_POST: u32 = 0x08;
+
+const BURST_START: u32 = 0x01;
+const BURST_TYPE_OFFSET: u32 = 1;
+const BURST_LEN_OFFSET: u32 = 3;
+const BURST_AUTO_PRE: u32 = 0x40;
+const BURST_AUTO_POST: u32 = 0x80;
+const BURST_PRE_ERROR: u32 = 0x0008_0000;
+const BURST_POST_ERROR: u32 = 0x0010_0000;
+const BURST_ADDRESS_ERROR: u32 = 0x0020_0000;
+const BURST_CLEAR: u32 = 0x0080_0000;
+
+const ERASE_START: u32 = 0x01;
+const ERASE_MASS: u32 = 0x02;
+const ERASE_TYPE_OFFSET: u32 = 2;
+const ERASE_ADDRESS_ERROR: u32 = 0x0004_0000;
+const ERASE_CLEAR: u32 = 0x0008_0000;
+
+const IFG_PRE_VERIFY: u32 = 0x0002;
+const IFG_POST_VERIFY: u32 = 0x0004;
+const IFG_PROGRAM: u32 = 0x0008;
+const IFG_BURST: u32 = 0x0010;
+const IFG_ERASE: u32 = 0x0020;
+const IFG_WORD_COMPOSITION: u32 = 0x0200;
+const IFG_ALL: u32 = 0x033F;
+
+const ERASED_WORD: u32 = 0xFFFF_FFFF;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// Address outside of the main and information memories
+    OutOfBounds,
+    /// Empty range
+    InvalidRange,
+    /// Address or length not aligned to the operation size
+    NotAligned,
+    /// The sector is write/erase protected
+    Protected,
+    /// Pre-program verify failure, the location was not erased
+    PreVerify,
+    /// Post-program verify failure, the location does not hold the data
+    PostVerify,
+    /// The sector is not blank after the erase
+    EraseVerify,
+    /// The data doesn't read back with the margin read modes (weakly programmed or erased bits)
+    MarginVerify,
+    /// The operation targeted reserved memory
+    ReservedAddress,
+    /// Full word mode writes crossed a 128-bit boundary before the word was composed
+    WordComposition,
+    /// The controller did not complete the operation in time
+    Timeout,
+}
+
+impl NorFlashError for Error {
+    fn kind(&self) -> NorFlashErrorKind {
+        match *self {
+            Error::OutOfBounds => NorFlashErrorKind::OutOfBounds,
+            Error::NotAligned => NorFlashErrorKind::NotAligned,
+            _ => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Memory {
+    Main = 0,
+    Information = 1,
+}
+
+/// Sector holding a flash address
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Sector {
+    pub memory: Memory,
+    pub bank: u8,
+    /// Sector number inside its bank
+    pub index: u8,
+}
+
+impl Sector {
+    /// Sector holding `address`, `None` outside of the main and information memories
+    pub fn containing(address: u32) -> Option<Sector> {
+        let (memory, offset, bank_size) = if address < MAIN_BASE + MAIN_SIZE {
+            (Memory::Main, address - MAIN_BASE, MAIN_BANK_SIZE)
+        } else if (INFO_BASE..INFO_BASE + INFO_SIZE).contains(&address) {
+            (Memory::Information, address - INFO_BASE, INFO_BANK_SIZE)
+        } else {
+            return None;
+        };
+
+        Some(Sector {
+            memory,
+            bank: (offset / bank_size) as u8,
+            index: ((offset % bank_size) / SECTOR_SIZE) as u8,
+        })
+    }
+
+    /// First address of the sector
+    pub fn address(&self) -> u32 {
+        let (base, bank_size) = match self.memory {
+            Memory::Main => (MAIN_BASE, MAIN_BANK_SIZE),
+            Memory::Information => (INFO_BASE, INFO_BANK_SIZE),
+        };
+
+        base + self.bank as u32 * bank_size + self.index as u32 * SECTOR_SIZE
+    }
+}
+
+/// Check that `address..address + length` is inside one memory and aligned to `align`
+pub(super) fn check_range(address: u32, length: u32, align: u32) -> Result<Sector, Error> {
+    if length == 0 {
+        return Sector::containing(address).ok_or(Error::OutOfBounds);
+    }
+
+    let last = address.checked_add(length - 1).ok_or(Error::OutOfBounds)?;
+    let first_sector = Sector::containing(address).ok_or(Error::OutOfBounds)?;
+    let last_sector = Sector::containing(last).ok_or(Error::OutOfBounds)?;
+
+    if first_sector.memory != last_sector.memory {
+        return Err(Error::OutOfBounds);
+    }
+
+    if address % align != 0 || length % align != 0 {
+        return Err(Error::NotAligned);
+    }
+
+    Ok(first_sector)
+}
+
+impl FlashControl<Defined> {
+    /// Let the controller verify the location is erased before (`pre`) and holds the data
+    /// after (`post`) every program operation
+    pub fn set_program_verify(&mut self, pre: bool, post: bool) -> &mut Self {
+        let mut value = 0;
+
+        if pre {
+            value |= PRG_VERIFY_PRE;
+        }
+
+        if post {
+            value |= PRG_VERIFY_POST;
+        }
+
+        self.flash.flctl_prg_ctlstat.modify(|r, w| unsafe {
+            w.bits((r.bits() & !(PRG_VERIFY_PRE | PRG_VERIFY_POST)) | value)
+        });
+        self
+    }
+
+    /// Read `buffer.len()` bytes starting at `address`
+    pub fn read(&self, address: u32, buffer: &mut [u8]) -> Result<(), Error> {
+        check_range(address, buffer.len() as u32, 1)?;
+
+        for (offset, byte) in buffer.iter_mut().enumerate() {
+            *byte = unsafe { ptr::read_volatile((address as usize + offset) as *const u8) };
+        }
+        Ok(())
+    }
+
+    /// Program one 32-bit word in immediate mode
+    pub(super) fn program_word(&mut self, address: u32, word: u32) -> Result<(), Error> {
+        let sector = check_range(address, 4, 4)?;
+        self.check_unprotected(sector)?;
+
+        self.start_program(0);
+        let result = self.wait_program(&[(address as *mut u32, word)]);
+        self.stop_program();
+
+        result
+    }
+
+    /// Program a full 128-bit flash word in full word mode
+    pub(super) fn program_full_word(&mut self, address: u32, data: [u32; 4]) -> Result<(), Error> {
+        let sector = check_range(address, FLASH_WORD_SIZE, FLASH_WORD_SIZE)?;
+        self.check_unprotected(sector)?;
+
+        let address = address as *mut u32;
+
+        self.start_program(PRG_FULL_WORD);
+        let result = unsafe {
+            self.wait_program(&[
+                (address, data[0]),
+                (address.add(1), data[1]),
+                (address.add(2), data[2]),
+                (address.add(3), data[3]),
+            ])
+        };
+        self.stop_program();
+
+        result
+    }
+
+    /// Program `data` (a multiple of 16 bytes) with burst operations of up to 64 bytes
+    pub(super) fn program_burst(&mut self, address: u32, data: &[u8]) -> Result<(), Error> {
+        let sector = check_range(address, data.len() as u32, FLASH_WORD_SIZE)?;
+        self.check_range_unprotected(address, data.len() as u32)?;
+
+        let mut address = address;
+        let mut data = data;
+
+        while !data.is_empty() {
+            let room = (BURST_MAX_WORDS * FLASH_WORD_SIZE)
+                - (address % (BURST_MAX_WORDS * FLASH_WORD_SIZE));
+            let length = room.min(data.len() as u32);
+
+            self.burst(sector.memory, address, &data[..length as usize])?;
+
+            address += length;
+            data = &data[length as usize..];
+        }
+
+        Ok(())
+    }
+
+    /// Erase the sector holding `address` and check it is blank
+    pub(super) fn erase_sector(&mut self, address: u32) -> Result<(), Error> {
+        let sector = Sector::containing(address).ok_or(Error::OutOfBounds)?;
+        self.check_unprotected(sector)?;
+
+        self.flash
+            .flctl_erase_sectaddr
+            .write(|w| unsafe { w.bits(sector.address()) });
+        self.erase(sector.memory, 0)?;
+
+        if !self.is_blank(sector.address(), SECTOR_SIZE) {
+            return Err(Error::EraseVerify);
+        }
+
+        Ok(())
+    }
+
+    /// Erase every unprotected sector of the main memory
+    pub(super) fn mass_erase(&mut self) -> Result<(), Error> {
+        self.erase(Memory::Main, ERASE_MASS)?;
+
+        for address in (MAIN_BASE..MAIN_BASE + MAIN_SIZE).step_by(SECTOR_SIZE as usize) {
+            let sector = Sector::containing(address).ok_or(Error::OutOfBounds)?;
+            if !self.is_sector_protected(sector) && !self.is_blank(address, SECTOR_SIZE) {
+                return Err(Error::EraseVerify);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check that `length` bytes starting at `address` are erased
+    pub fn is_blank(&self, address: u32, length: u32) -> bool {
+        (0..length / 4).all(|index| unsafe {
+            ptr::read_volatile((address as *const u32).add(index as usize)) == ERASED_WORD
+        })
+    }
+
+    fn check_unprotected(&self, sector: Sector) -> Result<(), Error> {
+        if self.is_sector_protected(sector) {
+            Err(Error::Protected)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_range_unprotected(&self, address: u32, length: u32) -> Result<(), Error> {
+        let mut sector_address = address - address % SECTOR_SIZE;
+
+        while sector_address < address + length {
+            self.check_unprotected(Sector::containing(sector_address).ok_or(Error::OutOfBounds)?)?;
+            sector_address += SECTOR_SIZE;
+        }
+
+        Ok(())
+    }
+
+    fn start_program(&mut self, mode: u32) {
+        self.flash
+            .flctl_clrifg
+            .write(|w| unsafe { w.bits(IFG_ALL) });
+        self.flash
+            .flctl_prg_ctlstat
+            .modify(|r, w| unsafe { w.bits((r.bits() & !PRG_FULL_WORD) | mode | PRG_ENABLE) });
+    }
+
+    fn stop_program(&mut self) {
+        self.flash
+            .flctl_prg_ctlstat
+            .modify(|r, w| unsafe { w.bits(r.bits() & !(PRG_ENABLE | PRG_FULL_WORD)) });
+    }
+
+    fn wait_program(&self, writes: &[(*mut u32, u32)]) -> Result<(), Error> {
+        self.execute(writes, IFG_PROGRAM | IFG_WORD_COMPOSITION)?;
+
+        let flags = self.flash.flctl_ifg.read().bits();
+
+        if flags & IFG_WORD_COMPOSITION != 0 {
+            Err(Error::WordComposition)
+        } else if flags & IFG_PRE_VERIFY != 0 {
+            Err(Error::PreVerify)
+        } else if flags & IFG_POST_VERIFY != 0 {
+            Err(Error::PostVerify)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn burst(&mut self, memory: Memory, address: u32, data: &[u8]) -> Result<(), Error> {
+        let data_registers =
+            unsafe { (FLCTL::ptr() as *mut u8).add(BURST_DATA_OFFSET) as *mut u32 };
+
+        for (index, chunk) in data.chunks_exact(4).enumerate() {
+            let word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            unsafe { ptr::write_volatile(data_registers.add(index), word) };
+        }
+
+        let verify = self.flash.flctl_prg_ctlstat.read().bits();
+        let mut control = ((memory as u32) << BURST_TYPE_OFFSET)
+            | ((data.len() as u32 / FLASH_WORD_SIZE) << BURST_LEN_OFFSET)
+            | BURST_START;
+
+        if verify & PRG_VERIFY_PRE != 0 {
+            control |= BURST_AUTO_PRE;
+        }
+
+        if verify & PRG_VERIFY_POST != 0 {
+            control |= BURST_AUTO_POST;
+        }
+
+        self.flash
+            .flctl_clrifg
+            .write(|w| unsafe { w.bits(IFG_ALL) });
+        self.flash
+            .flctl_prgbrst_ctlstat
+            .write(|w| unsafe { w.bits(BURST_CLEAR) });
+        self.flash
+            .flctl_prgbrst_startaddr
+            .write(|w| unsafe { w.bits(address) });
+
+        self.execute(
+            &[(self.flash.flctl_prgbrst_ctlstat.as_ptr(), control)],
+            IFG_BURST,
+        )?;
+
+        let status = self.flash.flctl_prgbrst_ctlstat.read().bits();
+        self.flash
+            .flctl_prgbrst_ctlstat
+            .write(|w| unsafe { w.bits(BURST_CLEAR) });
+
+        if status & BURST_ADDRESS_ERROR != 0 {
+            Err(Error::ReservedAddress)
+        } else if status & BURST_PRE_ERROR != 0 {
+            Err(Error::PreVerify)
+        } else if status & BURST_POST_ERROR != 0 {
+            Err(Error::PostVerify)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn erase(&mut self, memory: Memory, mode: u32) -> Result<(), Error> {
+        self.flash
+            .flctl_clrifg
+            .write(|w| unsafe { w.bits(IFG_ALL) });
+        self.flash
+            .flctl_erase_ctlstat
+            .write(|w| unsafe { w.bits(ERASE_CLEAR) });
+
+        self.execute(
+            &[(
+                self.flash.flctl_erase_ctlstat.as_ptr(),
+                ((memory as u32) << ERASE_TYPE_OFFSET) | mode | ERASE_START,
+            )],
+            IFG_ERASE,
+        )?;
+
+        let status = self.flash.flctl_erase_ctlstat.read().bits();
+        self.flash
+            .flctl_erase_ctlstat
+            .write(|w| unsafe { w.bits(ERASE_CLEAR) });
+
+        if status & ERASE_ADDRESS_ERROR != 0 {
+            Err(Error::ReservedAddress)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Start an operation with `writes` and wait for `flag`, from SRAM
+    fn execute(&self, writes: &[(*mut u32, u32)], flag: u32) -> Result<(), Error> {
+        if ram::execute(
+            writes,
+            self.flash.flctl_ifg.as_ptr(),
+            flag,
+            OPERATION_TIMEOUT,
+        ) {
+            Ok(())
+        } else {
+            Err(Error::Timeout)
+        }
+    }
+}
+
+impl ErrorType for FlashControl<Defined> {
+    type Error = Error;
+}
+
+/// Main memory, offsets are flash addresses
+impl ReadNorFlash for FlashControl<Defined> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        if offset.saturating_add(bytes.len() as u32) > MAIN_SIZE {
+            return Err(Error::OutOfBounds);
+        }
+
+        FlashControl::read(self, MAIN_BASE + offset, bytes)
+    }
+
+    fn capacity(&self) -> usize {
+        MAIN_SIZE as usize
+    }
+}
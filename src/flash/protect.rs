@@ -0,0 +1,411 @@
+//! HAL library for Flash write/erase protection - MSP432P401R
+
+/*
+-- WRITE/ERASE PROTECTION: --
+
+Every sector has a protection bit (FLCTL_BANKx_MAIN_WEPROT: 32 sectors per bank,
+FLCTL_BANKx_INFO_WEPROT: 2 sectors per bank), all the sectors are protected after reset. Program
+and erase operations on a protected sector fail with `Error::Protected`.
+
+`FlashControl::unlock` unprotects a range of sectors and returns an `Unlocked` handle, the only way
+to program or erase the flash: `FlashControl` itself can only protect sectors. The protection bits
+saved by `unlock` are restored when the handle is dropped, so sectors that are never unlocked (a
+bootloader, for instance) can't be overwritten by mistake.
+
+`Unlocked` is a `NorFlash` over the main memory, `Unlocked::info_memory` a `NorFlash` over the
+information memory (offsets from 0x0020_0000, the unlocked range must be in it). In the
+information memory, bank 0 holds the flash mailbox (sector 0) and the TLV table (sector 1), bank 1
+the factory bootstrap loader: only use them when these are not needed.
+
+-- IP PROTECTION: --
+
+The IP protected secure zones, the JTAG/SWD lock and the factory reset are configured by the boot
+code, from commands left in the flash mailbox (start of the information memory, 0x0020_0000) and
+applied on the next reset (see the device security chapter of the reference manual). The secure
+zone commands are written by `FlashControl::write_mailbox` (see `mailbox.rs`), the resulting state
+is reported by `sysctl::SystemControl::security_status`.
+*/
+
+use embedded_storage::nor_flash::{ErrorType, NorFlash, ReadNorFlash};
+
+use super::program::check_range;
+use super::{
+    Bank, Defined, Error, FlashControl, Memory, Sector, FLASH_WORD_SIZE, INFO_BANK_SIZE, INFO_BASE,
+    INFO_SIZE, MAIN_BANK_SIZE, MAIN_BASE, MAIN_SIZE, SECTOR_SIZE,
+};
+
+impl FlashControl<Defined> {
+    /// Protect (bit set) or unprotect (bit cleared) the sectors of `bank`, bit n is sector n
+    fn set_bank_protection(&mut self, memory: Memory, bank: Bank, sectors: u32) -> &mut Self {
+        let sectors = sectors & sector_mask(memory);
+
+        match (memory, bank) {
+            (Memory::Main, Bank::Bank0) => self
+                .flash
+                .flctl_bank0_main_weprot
+                .write(|w| unsafe { w.bits(sectors) }),
+            (Memory::Main, Bank::Bank1) => self
+                .flash
+                .flctl_bank1_main_weprot
+                .write(|w| unsafe { w.bits(sectors) }),
+            (Memory::Information, Bank::Bank0) => self
+                .flash
+                .flctl_bank0_info_weprot
+                .write(|w| unsafe { w.bits(sectors) }),
+            (Memory::Information, Bank::Bank1) => self
+                .flash
+                .flctl_bank1_info_weprot
+                .write(|w| unsafe { w.bits(sectors) }),
+        }
+        self
+    }
+
+    /// Protected sectors of `bank`, bit n is sector n
+    pub fn bank_protection(&self, memory: Memory, bank: Bank) -> u32 {
+        let sectors = match (memory, bank) {
+            (Memory::Main, Bank::Bank0) => self.flash.flctl_bank0_main_weprot.read().bits(),
+            (Memory::Main, Bank::Bank1) => self.flash.flctl_bank1_main_weprot.read().bits(),
+            (Memory::Information, Bank::Bank0) => self.flash.flctl_bank0_info_weprot.read().bits(),
+            (Memory::Information, Bank::Bank1) => self.flash.flctl_bank1_info_weprot.read().bits(),
+        };
+
+        sectors & sector_mask(memory)
+    }
+
+    /// Protect every sector of both memories (reset state)
+    pub fn protect_all(&mut self) -> &mut Self {
+        for (memory, bank) in BANKS {
+            self.set_bank_protection(memory, bank, sector_mask(memory));
+        }
+        self
+    }
+
+    /// Allow (or not) program and erase operations on `sector`
+    fn set_sector_protection(&mut self, sector: Sector, protect: bool) -> &mut Self {
+        let bank = bank(&sector);
+        let mut sectors = self.bank_protection(sector.memory, bank);
+
+        if protect {
+            sectors |= 1 << sector.index;
+        } else {
+            sectors &= !(1 << sector.index);
+        }
+
+        self.set_bank_protection(sector.memory, bank, sectors)
+    }
+
+    #[inline]
+    pub fn is_sector_protected(&self, sector: Sector) -> bool {
+        self.bank_protection(sector.memory, bank(&sector)) & (1 << sector.index) != 0
+    }
+
+    /// Protect (or not) every sector holding a byte of `address..address + length`
+    fn set_protection(
+        &mut self,
+        address: u32,
+        length: u32,
+        protect: bool,
+    ) -> Result<&mut Self, Error> {
+        check_range(address, length, 1)?;
+
+        for sector in sectors(address, length) {
+            self.set_sector_protection(sector, protect);
+        }
+
+        Ok(self)
+    }
+
+    /// Unprotect the sectors holding `address..address + length` until the handle is dropped
+    pub fn unlock(&mut self, address: u32, length: u32) -> Result<Unlocked<'_>, Error> {
+        let (start, end) = unlock_window(address, length)?;
+        let saved = BANKS.map(|(memory, bank)| self.bank_protection(memory, bank));
+        self.set_protection(address, length, false)?;
+
+        Ok(Unlocked {
+            flash: self,
+            start,
+            end,
+            saved,
+        })
+    }
+}
+
+/// Protection registers, in the order of `Unlocked::saved`
+const BANKS: [(Memory, Bank); 4] = [
+    (Memory::Main, Bank::Bank0),
+    (Memory::Main, Bank::Bank1),
+    (Memory::Information, Bank::Bank0),
+    (Memory::Information, Bank::Bank1),
+];
+
+/// Unprotected range of sectors, the previous protection is restored when dropped
+pub struct Unlocked<'a> {
+    flash: &'a mut FlashControl<Defined>,
+    start: u32,
+    end: u32,
+    saved: [u32; 4],
+}
+
+impl Unlocked<'_> {
+    pub fn program_word(&mut self, address: u32, word: u32) -> Result<(), Error> {
+        self.check(address, 4)?;
+        self.flash.program_word(address, word)
+    }
+
+    pub fn program_full_word(&mut self, address: u32, data: [u32; 4]) -> Result<(), Error> {
+        self.check(address, 16)?;
+        self.flash.program_full_word(address, data)
+    }
+
+    pub fn program_burst(&mut self, address: u32, data: &[u8]) -> Result<(), Error> {
+        self.check(address, data.len() as u32)?;
+        self.flash.program_burst(address, data)
+    }
+
+    pub fn program_verified(&mut self, address: u32, data: &[u8]) -> Result<(), Error> {
+        self.check(address, data.len() as u32)?;
+        self.flash.program_verified(address, data)
+    }
+
+    pub fn erase_sector(&mut self, address: u32) -> Result<(), Error> {
+        self.check(address, 1)?;
+        self.flash.erase_sector(address)
+    }
+
+    /// Erase the whole main memory, which must be the unlocked range
+    pub fn mass_erase(&mut self) -> Result<(), Error> {
+        self.check(MAIN_BASE, MAIN_SIZE)?;
+        self.flash.mass_erase()
+    }
+
+    /// `NorFlash` view of the information memory, only the unlocked sectors can be written or
+    /// erased
+    #[inline]
+    pub fn info_memory(&mut self) -> InfoMemory<'_, Self> {
+        InfoMemory::new(self)
+    }
+
+    /// Restore the previous protection
+    #[inline]
+    pub fn lock(self) {}
+
+    /// Check that `address..address + length` is in the unlocked sectors
+    fn check(&self, address: u32, length: u32) -> Result<(), Error> {
+        match address.checked_add(length) {
+            Some(end) if address >= self.start && end <= self.end => Ok(()),
+            _ => Err(Error::Protected),
+        }
+    }
+}
+
+impl ErrorType for Unlocked<'_> {
+    type Error = Error;
+}
+
+/// Main memory, offsets are flash addresses, only the unlocked sectors can be written or erased
+impl ReadNorFlash for Unlocked<'_> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        if offset.saturating_add(bytes.len() as u32) > MAIN_SIZE {
+            return Err(Error::OutOfBounds);
+        }
+
+        self.flash.read(MAIN_BASE + offset, bytes)
+    }
+
+    fn capacity(&self) -> usize {
+        MAIN_SIZE as usize
+    }
+}
+
+impl NorFlash for Unlocked<'_> {
+    const WRITE_SIZE: usize = FLASH_WORD_SIZE as usize;
+    const ERASE_SIZE: usize = SECTOR_SIZE as usize;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        if from > to || to > MAIN_SIZE {
+            return Err(Error::OutOfBounds);
+        }
+
+        if from % SECTOR_SIZE != 0 || to % SECTOR_SIZE != 0 {
+            return Err(Error::NotAligned);
+        }
+
+        self.check(MAIN_BASE + from, to - from)?;
+
+        for address in (from..to).step_by(SECTOR_SIZE as usize) {
+            self.flash.erase_sector(MAIN_BASE + address)?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        if offset.saturating_add(bytes.len() as u32) > MAIN_SIZE {
+            return Err(Error::OutOfBounds);
+        }
+
+        self.program_burst(MAIN_BASE + offset, bytes)
+    }
+}
+
+/// Flash operations by address, an `InfoMemory` view is built on
+pub trait FlashSectors {
+    fn read(&mut self, address: u32, bytes: &mut [u8]) -> Result<(), Error>;
+    fn program(&mut self, address: u32, bytes: &[u8]) -> Result<(), Error>;
+    fn erase_sector(&mut self, address: u32) -> Result<(), Error>;
+}
+
+impl FlashSectors for Unlocked<'_> {
+    #[inline]
+    fn read(&mut self, address: u32, bytes: &mut [u8]) -> Result<(), Error> {
+        self.flash.read(address, bytes)
+    }
+
+    #[inline]
+    fn program(&mut self, address: u32, bytes: &[u8]) -> Result<(), Error> {
+        self.program_burst(address, bytes)
+    }
+
+    #[inline]
+    fn erase_sector(&mut self, address: u32) -> Result<(), Error> {
+        Unlocked::erase_sector(self, address)
+    }
+}
+
+/// Information memory, offsets are relative to `INFO_BASE`
+pub struct InfoMemory<'a, F: FlashSectors> {
+    flash: &'a mut F,
+}
+
+impl<'a, F: FlashSectors> InfoMemory<'a, F> {
+    #[inline]
+    pub fn new(flash: &'a mut F) -> Self {
+        InfoMemory { flash }
+    }
+
+    /// Flash address of `offset..offset + length`
+    fn address(offset: u32, length: usize) -> Result<u32, Error> {
+        match offset.checked_add(length as u32) {
+            Some(end) if length as u32 <= INFO_SIZE && end <= INFO_SIZE => Ok(INFO_BASE + offset),
+            _ => Err(Error::OutOfBounds),
+        }
+    }
+}
+
+impl<F: FlashSectors> ErrorType for InfoMemory<'_, F> {
+    type Error = Error;
+}
+
+impl<F: FlashSectors> ReadNorFlash for InfoMemory<'_, F> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+        let address = Self::address(offset, bytes.len())?;
+        self.flash.read(address, bytes)
+    }
+
+    fn capacity(&self) -> usize {
+        INFO_SIZE as usize
+    }
+}
+
+impl<F: FlashSectors> NorFlash for InfoMemory<'_, F> {
+    const WRITE_SIZE: usize = FLASH_WORD_SIZE as usize;
+    const ERASE_SIZE: usize = SECTOR_SIZE as usize;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+        if from > to {
+            return Err(Error::OutOfBounds);
+        }
+
+        let start = Self::address(from, (to - from) as usize)?;
+
+        if from % SECTOR_SIZE != 0 || to % SECTOR_SIZE != 0 {
+            return Err(Error::NotAligned);
+        }
+
+        for address in (start..start + (to - from)).step_by(SECTOR_SIZE as usize) {
+            self.flash.erase_sector(address)?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+        let address = Self::address(offset, bytes.len())?;
+        self.flash.program(address, bytes)
+    }
+}
+
+impl Drop for Unlocked<'_> {
+    fn drop(&mut self) {
+        for (&(memory, bank), &sectors) in BANKS.iter().zip(self.saved.iter()) {
+            self.flash.set_bank_protection(memory, bank, sectors);
+        }
+    }
+}
+
+/// Sector aligned range of the sectors holding `address..address + length`
+fn unlock_window(address: u32, length: u32) -> Result<(u32, u32), Error> {
+    if length == 0 {
+        return Err(Error::InvalidRange);
+    }
+
+    check_range(address, length, 1)?;
+
+    let mut unlocked = sectors(address, length).map(|sector| sector.address());
+    let start = unlocked.next().ok_or(Error::OutOfBounds)?;
+    let end = unlocked.last().unwrap_or(start) + SECTOR_SIZE;
+
+    Ok((start, end))
+}
+
+/// Sectors holding a byte of `address..address + length` (a checked range)
+fn sectors(address: u32, length: u32) -> impl Iterator<Item = Sector> {
+    let first = address - address % SECTOR_SIZE;
+
+    (first..address + length)
+        .step_by(SECTOR_SIZE as usize)
+        .filter_map(Sector::containing)
+}
+
+#[inline]
+fn bank(sector: &Sector) -> Bank {
+    if sector.bank == 0 {
+        Bank::Bank0
+    } else {
+        Bank::Bank1
+    }
+}
+
+/// Protection bits of a bank
+#[inline]
+fn sector_mask(memory: Memory) -> u32 {
+    let sectors = match memory {
+        Memory::Main => MAIN_BANK_SIZE / SECTOR_SIZE,
+        Memory::Information => INFO_BANK_SIZE / SECTOR_SIZE,
+    };
+
+    u32::MAX >> (32 - sectors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlocked_window() {
+        assert_eq!(unlock_window(0x0000_1010, 0), Err(Error::InvalidRange));
+        assert_eq!(unlock_window(0x0000_1000, 0), Err(Error::InvalidRange));
+
+        assert_eq!(unlock_window(0x0000_1010, 0x10), Ok((0x0000_1000, 0x0000_2000)));
+        assert_eq!(unlock_window(0x0000_1FFF, 2), Ok((0x0000_1000, 0x0000_3000)));
+        assert_eq!(unlock_window(0x0001_F000, 0x2000), Ok((0x0001_F000, 0x0002_1000)));
+        assert_eq!(unlock_window(INFO_BASE + 0x2000, 0x2000), Ok((0x0020_2000, 0x0020_4000)));
+
+        assert_eq!(unlock_window(MAIN_SIZE - 0x10, 0x20), Err(Error::OutOfBounds));
+        assert_eq!(unlock_window(INFO_BASE + INFO_SIZE, 4), Err(Error::OutOfBounds));
+    }
+}
@@ -0,0 +1,240 @@
+//! HAL library for SRAM resident Flash routines - MSP432P401R
+
+/*
+A bank can't be read while it's being programmed or erased: instruction fetches from the bank stall
+until the operation completes, so the code starting and waiting for an operation must not run from
+the bank it programs or erases.
+
+`execute` runs the last register (or flash) writes starting an operation and the polling of the
+FLCTL_IFG flags from SRAM, with the interrupts disabled (their handlers live in flash as well). It
+uses nothing but volatile accesses, so no flash code is called while the operation is running, and
+every program and erase operation of `FlashControl` goes through it: any sector of either bank can
+be updated, including the one the application runs from.
+
+The same goes for the margin reads (see `read.rs`): `margin_compare` switches a bank to the margin
+read modes, compares the data and switches it back, so the code is never fetched from a bank in a
+margin mode.
+
+-- PLACEMENT: --
+
+The routines are placed in the `.ramfunc` section, linked in RAM and copied from flash at startup
+by `ramfunc.x` (included by `memory.x`, an application providing its own `memory.x` must include it
+too). The same attribute can be used for application functions:
+
+    #[inline(never)]
+    #[link_section = ".ramfunc"]
+    fn runs_from_sram() { ... }
+
+Calls from flash to SRAM (and back) are out of the BL range, the linker inserts long branch veneers.
+The routines don't call any function, even without optimizations: the helpers they use are
+`#[inline(always)]` (unlike the `core::ptr` functions), the memory accesses are single load and
+store instructions and the arithmetic wraps (no overflow checks calling the panic handler).
+*/
+
+#[cfg(target_arch = "arm")]
+use core::arch::asm;
+
+use cortex_m::interrupt;
+
+/// Perform `writes` in order, then wait for one of the `flags` bits of `ifg`, from SRAM
+///
+/// Returns false on timeout.
+pub(super) fn execute(
+    writes: &[(*mut u32, u32)],
+    ifg: *const u32,
+    flags: u32,
+    timeout: u32,
+) -> bool {
+    interrupt::free(|_| unsafe { run(writes.as_ptr(), writes.len(), ifg, flags, timeout) })
+}
+
+/// Outcome of `margin_compare`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(super) enum Compare {
+    Match,
+    Mismatch,
+    Timeout,
+}
+
+/// Compare `data` with the `data.len()` bytes at `address` with each of the `modes` applied to the
+/// bank read control register `rdctl`, then apply `restore`, from SRAM
+///
+/// A mode is the value written to `rdctl` and the `status_mask` bits it reads back once applied.
+pub(super) fn margin_compare(
+    rdctl: *mut u32,
+    modes: &[(u32, u32)],
+    restore: (u32, u32),
+    status_mask: u32,
+    address: *const u8,
+    data: &[u8],
+    timeout: u32,
+) -> Compare {
+    interrupt::free(|_| unsafe {
+        compare(
+            rdctl,
+            modes.as_ptr(),
+            modes.len(),
+            restore,
+            status_mask,
+            address,
+            data.as_ptr(),
+            data.len(),
+            timeout,
+        )
+    })
+}
+
+#[inline(never)]
+#[link_section = ".ramfunc"]
+unsafe fn run(
+    writes: *const (*mut u32, u32),
+    count: usize,
+    ifg: *const u32,
+    flags: u32,
+    timeout: u32,
+) -> bool {
+    let mut index = 0;
+
+    while index < count {
+        let (address, value) = *element(writes, index);
+        store32(address, value);
+        index = index.wrapping_add(1);
+    }
+
+    let mut remaining = timeout;
+
+    while remaining > 0 {
+        if load32(ifg) & flags != 0 {
+            return true;
+        }
+
+        remaining = remaining.wrapping_sub(1);
+    }
+
+    false
+}
+
+#[allow(clippy::too_many_arguments)]
+#[inline(never)]
+#[link_section = ".ramfunc"]
+unsafe fn compare(
+    rdctl: *mut u32,
+    modes: *const (u32, u32),
+    count: usize,
+    restore: (u32, u32),
+    status_mask: u32,
+    address: *const u8,
+    data: *const u8,
+    length: usize,
+    timeout: u32,
+) -> Compare {
+    let mut result = Compare::Match;
+    let mut index = 0;
+
+    while index < count {
+        let (value, status) = *element(modes, index);
+
+        if !apply(rdctl, value, status, status_mask, timeout) {
+            result = Compare::Timeout;
+            break;
+        }
+
+        let mut offset = 0;
+
+        while offset < length {
+            if load8(element(address, offset)) != load8(element(data, offset)) {
+                result = Compare::Mismatch;
+                break;
+            }
+
+            offset = offset.wrapping_add(1);
+        }
+
+        if offset < length {
+            break;
+        }
+
+        index = index.wrapping_add(1);
+    }
+
+    if !apply(rdctl, restore.0, restore.1, status_mask, timeout) {
+        result = Compare::Timeout;
+    }
+
+    result
+}
+
+/// Write `value` to `rdctl` and wait for the `mask` bits to read `status`
+#[inline(always)]
+unsafe fn apply(rdctl: *mut u32, value: u32, status: u32, mask: u32, timeout: u32) -> bool {
+    store32(rdctl, value);
+
+    let mut remaining = timeout;
+
+    while remaining > 0 {
+        if load32(rdctl) & mask == status {
+            return true;
+        }
+
+        remaining = remaining.wrapping_sub(1);
+    }
+
+    false
+}
+
+/// Address of the element `index` of the array at `base` (no `ptr::add` call)
+#[inline(always)]
+fn element<T>(base: *const T, index: usize) -> *const T {
+    (base as usize).wrapping_add(index.wrapping_mul(core::mem::size_of::<T>())) as *const T
+}
+
+#[cfg(target_arch = "arm")]
+#[inline(always)]
+unsafe fn load8(address: *const u8) -> u8 {
+    let value: u32;
+    asm!(
+        "ldrb {}, [{}]",
+        out(reg) value,
+        in(reg) address,
+        options(nostack, preserves_flags, readonly)
+    );
+    value as u8
+}
+
+#[cfg(target_arch = "arm")]
+#[inline(always)]
+unsafe fn load32(address: *const u32) -> u32 {
+    let value;
+    asm!(
+        "ldr {}, [{}]",
+        out(reg) value,
+        in(reg) address,
+        options(nostack, preserves_flags, readonly)
+    );
+    value
+}
+
+#[cfg(target_arch = "arm")]
+#[inline(always)]
+unsafe fn store32(address: *mut u32, value: u32) {
+    asm!("str {}, [{}]", in(reg) value, in(reg) address, options(nostack, preserves_flags));
+}
+
+// Other architectures only build the routines (host tests), they never run them
+#[cfg(not(target_arch = "arm"))]
+#[inline(always)]
+unsafe fn load8(address: *const u8) -> u8 {
+    core::ptr::read_volatile(address)
+}
+
+#[cfg(not(target_arch = "arm"))]
+#[inline(always)]
+unsafe fn load32(address: *const u32) -> u32 {
+    core::ptr::read_volatile(address)
+}
+
+#[cfg(not(target_arch = "arm"))]
+#[inline(always)]
+unsafe fn store32(address: *mut u32, value: u32) {
+    core::ptr::write_volatile(address, value)
+}
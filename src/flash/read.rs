@@ -0,0 +1,192 @@
+//! HAL library for Flash read modes and read buffering - MSP432P401R
+
+/*
+-- READ MODES (FLCTL_BANKx_RDCTL.RD_MODE): --
+
+NORMAL --> Regular reads;
+MARGIN 0 / 0B --> Reads with a stricter reference for the programmed (0) bits, a weakly programmed
+                  bit reads as 1;
+MARGIN 1 / 1B --> Reads with a stricter reference for the erased (1) bits, a weakly erased (or
+                  disturbed) bit reads as 0;
+PROGRAM VERIFY --> Reads used by the controller to verify a program operation;
+ERASE VERIFY --> Reads used by the controller to verify an erase operation;
+LEAKAGE VERIFY --> Reads used to detect leaking (column) cells;
+
+The read mode applies to the whole bank, code and constants fetched from a bank in a margin mode
+are read with the same margin: with `set_read_mode`, change the mode of the bank the code is not
+running from. `verify_margin` switches the modes, compares the data and restores the normal mode
+from SRAM (see `ram.rs`), so it can verify either bank.
+
+-- READ BUFFERING: --
+
+BUFI --> Instruction fetches go through a 128-bit buffer;
+BUFD --> Data reads go through a 128-bit buffer;
+
+The buffers are bypassed while the margin verification is running.
+*/
+
+use super::ram::{self, Compare};
+use super::{Defined, Error, FlashControl, Sector};
+
+const READ_MODE_MASK: u32 = 0x0000_000F;
+const READ_MODE_STATUS_OFFSET: u32 = 16;
+const READ_MODE_STATUS_MASK: u32 = 0x000F_0000;
+const BUFFER_INSTRUCTION: u32 = 0x10;
+const BUFFER_DATA: u32 = 0x20;
+const READ_MODE_TIMEOUT: u32 = 10_000;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ReadMode {
+    Normal = 0x00,
+    Margin0 = 0x01,
+    Margin1 = 0x02,
+    ProgramVerify = 0x03,
+    EraseVerify = 0x04,
+    LeakageVerify = 0x05,
+    Margin0B = 0x09,
+    Margin1B = 0x0A,
+}
+
+impl ReadMode {
+    fn from_bits(bits: u32) -> Option<ReadMode> {
+        match bits {
+            0x00 => Some(ReadMode::Normal),
+            0x01 => Some(ReadMode::Margin0),
+            0x02 => Some(ReadMode::Margin1),
+            0x03 => Some(ReadMode::ProgramVerify),
+            0x04 => Some(ReadMode::EraseVerify),
+            0x05 => Some(ReadMode::LeakageVerify),
+            0x09 => Some(ReadMode::Margin0B),
+            0x0A => Some(ReadMode::Margin1B),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Bank {
+    Bank0,
+    Bank1,
+}
+
+impl FlashControl<Defined> {
+    /// Set the read mode of `bank` and wait for the controller to apply it
+    pub fn set_read_mode(&mut self, bank: Bank, mode: ReadMode) -> Result<&mut Self, Error> {
+        self.modify_read_control(bank, mode as u32, READ_MODE_MASK);
+
+        for _ in 0..READ_MODE_TIMEOUT {
+            if self.read_mode(bank) == Some(mode) {
+                return Ok(self);
+            }
+        }
+
+        Err(Error::Timeout)
+    }
+
+    /// Read mode currently applied to `bank`
+    pub fn read_mode(&self, bank: Bank) -> Option<ReadMode> {
+        let status = (self.read_control(bank) & READ_MODE_STATUS_MASK) >> READ_MODE_STATUS_OFFSET;
+        ReadMode::from_bits(status)
+    }
+
+    /// Enable (or not) the instruction and data read buffers of `bank`
+    pub fn set_read_buffering(&mut self, bank: Bank, instruction: bool, data: bool) -> &mut Self {
+        let mut value = 0;
+
+        if instruction {
+            value |= BUFFER_INSTRUCTION;
+        }
+
+        if data {
+            value |= BUFFER_DATA;
+        }
+
+        self.modify_read_control(bank, value, BUFFER_INSTRUCTION | BUFFER_DATA);
+        self
+    }
+
+    /// Instruction and data read buffers state of `bank`
+    pub fn read_buffering(&self, bank: Bank) -> (bool, bool) {
+        let control = self.read_control(bank);
+        (
+            control & BUFFER_INSTRUCTION != 0,
+            control & BUFFER_DATA != 0,
+        )
+    }
+
+    /// Check that `data` reads back at `address` with both the margin 0 and margin 1 modes, to
+    /// detect weakly programmed or erased bits
+    ///
+    /// `data` is read while the banks are in a margin mode, keep it out of them (in SRAM).
+    pub fn verify_margin(&mut self, address: u32, data: &[u8]) -> Result<(), Error> {
+        let first = Sector::containing(address).ok_or(Error::OutOfBounds)?;
+        let last = address
+            .checked_add(data.len().saturating_sub(1) as u32)
+            .and_then(Sector::containing)
+            .ok_or(Error::OutOfBounds)?;
+
+        for bank in [Bank::Bank0, Bank::Bank1] {
+            if (bank as u8) < first.bank || (bank as u8) > last.bank {
+                continue;
+            }
+
+            // Buffers bypassed while the margin modes are applied
+            let control = self.read_control(bank) & !READ_MODE_STATUS_MASK;
+            let margin = control & !(READ_MODE_MASK | BUFFER_INSTRUCTION | BUFFER_DATA);
+            let mode = |mode: ReadMode| {
+                (margin | mode as u32, (mode as u32) << READ_MODE_STATUS_OFFSET)
+            };
+
+            let result = ram::margin_compare(
+                self.read_control_ptr(bank),
+                &[mode(ReadMode::Margin0), mode(ReadMode::Margin1)],
+                (control, (control & READ_MODE_MASK) << READ_MODE_STATUS_OFFSET),
+                READ_MODE_STATUS_MASK,
+                address as *const u8,
+                data,
+                READ_MODE_TIMEOUT,
+            );
+
+            match result {
+                Compare::Match => {}
+                Compare::Mismatch => return Err(Error::MarginVerify),
+                Compare::Timeout => return Err(Error::Timeout),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Program `data` in burst mode, then verify it with margin reads
+    pub(super) fn program_verified(&mut self, address: u32, data: &[u8]) -> Result<(), Error> {
+        self.program_burst(address, data)?;
+        self.verify_margin(address, data)
+    }
+
+    fn read_control(&self, bank: Bank) -> u32 {
+        match bank {
+            Bank::Bank0 => self.flash.flctl_bank0_rdctl.read().bits(),
+            Bank::Bank1 => self.flash.flctl_bank1_rdctl.read().bits(),
+        }
+    }
+
+    fn read_control_ptr(&self, bank: Bank) -> *mut u32 {
+        match bank {
+            Bank::Bank0 => self.flash.flctl_bank0_rdctl.as_ptr(),
+            Bank::Bank1 => self.flash.flctl_bank1_rdctl.as_ptr(),
+        }
+    }
+
+    fn modify_read_control(&self, bank: Bank, value: u32, mask: u32) {
+        match bank {
+            Bank::Bank0 => self
+                .flash
+                .flctl_bank0_rdctl
+                .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | value) }),
+            Bank::Bank1 => self
+                .flash
+                .flctl_bank1_rdctl
+                .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | value) }),
+        }
+    }
+}
@@ -0,0 +1,533 @@
+//! HAL library for dual-bank firmware updates - MSP432P401R
+
+/*
+-- LAYOUT: --
+
+BOOTLOADER --> 0x0000_0000 - 0x0000_3FFF, first 4 sectors of bank 0 (never unlocked by the update);
+SLOT A --> 0x0000_4000 - 0x0001_FFFF, rest of bank 0;
+SLOT B --> 0x0002_0000 - 0x0003_BFFF, bank 1 (the last 4 sectors are left to the application);
+
+A slot starts with a 512 bytes header area, followed by the image (vector table first, so the
+image of slot A is linked at 0x0000_4200 and the image of slot B at 0x0002_0200):
+
+0x00 --> Image header: magic, version, image size, image CRC32, load address, header CRC32;
+0x20 --> Ready flag, programmed once the image has been written and verified;
+0x30 --> Revoked flag, programmed to stop booting the image;
+
+-- UPDATE: --
+
+The image is written to the slot the application is not running from (the flash operations run
+from SRAM, see `ram.rs`). Once the whole file (header area and image) is written, the image CRC32
+is checked against the header and the ready flag is set.
+
+-- BOOT: --
+
+The bootloader boots the ready, not revoked, slot with the highest version (`select`), after
+checking its CRC32, by relocating the vector table (VTOR) and jumping to its reset handler.
+
+The header parsing and the slot selection only work on byte slices, so the image packaging tools
+can share them on the host.
+*/
+
+use core::slice;
+
+use cortex_m::peripheral::SCB;
+
+use super::{Defined, FlashControl, Unlocked, FLASH_WORD_SIZE, MAIN_BANK_SIZE, SECTOR_SIZE};
+use crate::crc::{Crc32Hasher, CrcEngine};
+
+pub const BOOTLOADER_SIZE: u32 = 0x0000_4000;
+pub const SLOT_SIZE: u32 = MAIN_BANK_SIZE - BOOTLOADER_SIZE;
+pub const HEADER_AREA_SIZE: u32 = 0x0000_0200;
+
+const IMAGE_MAGIC: u32 = 0x3141_544F;
+const READY_OFFSET: usize = 0x20;
+const REVOKED_OFFSET: usize = 0x30;
+const FLAGS_END: usize = 0x40;
+const READY: u32 = 0x5944_5252;
+const REVOKED: u32 = 0x0000_0000;
+const ERASED_WORD: u32 = 0xFFFF_FFFF;
+const BUFFER_SIZE: usize = 4 * FLASH_WORD_SIZE as usize;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    Flash(super::Error),
+    /// No image header
+    Magic,
+    HeaderChecksum,
+    /// The image doesn't fit the slot, or the written file is shorter than the image
+    Size,
+    /// The image is not linked for the slot
+    LoadAddress,
+    ImageChecksum,
+    /// The slot holds the running application
+    RunningSlot,
+}
+
+impl From<super::Error> for Error {
+    fn from(error: super::Error) -> Self {
+        Error::Flash(error)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Slot {
+    A,
+    B,
+}
+
+impl Slot {
+    /// Start address of the slot (header area)
+    pub const fn address(&self) -> u32 {
+        match self {
+            Slot::A => BOOTLOADER_SIZE,
+            Slot::B => MAIN_BANK_SIZE,
+        }
+    }
+
+    /// Address the image of the slot is linked at
+    pub const fn vector_table(&self) -> u32 {
+        self.address() + HEADER_AREA_SIZE
+    }
+
+    pub const fn other(&self) -> Slot {
+        match self {
+            Slot::A => Slot::B,
+            Slot::B => Slot::A,
+        }
+    }
+
+    pub fn containing(address: u32) -> Option<Slot> {
+        [Slot::A, Slot::B]
+            .iter()
+            .copied()
+            .find(|slot| (slot.address()..slot.address() + SLOT_SIZE).contains(&address))
+    }
+
+    /// Slot of the running application, from the vector table address (VTOR)
+    pub fn running() -> Option<Slot> {
+        let scb = unsafe { &*SCB::PTR };
+        Slot::containing(scb.vtor.read())
+    }
+
+    /// Parse the header area of the slot
+    pub fn status<E: CrcEngine<Word = u32>>(
+        &self,
+        crc: &mut Crc32Hasher<E>,
+    ) -> Result<SlotStatus, Error> {
+        SlotStatus::parse(self.memory(0, FLAGS_END as u32), crc)
+    }
+
+    /// Check the CRC32 of the image described by `header`
+    pub fn verify<E: CrcEngine<Word = u32>>(
+        &self,
+        header: &ImageHeader,
+        crc: &mut Crc32Hasher<E>,
+    ) -> Result<(), Error> {
+        header.check(*self)?;
+        verify_image(header, self.memory(HEADER_AREA_SIZE, header.size), crc)
+    }
+
+    fn memory(&self, offset: u32, length: u32) -> &'static [u8] {
+        unsafe { slice::from_raw_parts((self.address() + offset) as *const u8, length as usize) }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ImageHeader {
+    pub version: u32,
+    /// Image size in bytes, without the header area
+    pub size: u32,
+    /// CRC32 (ISO-HDLC) of the image
+    pub checksum: u32,
+    /// Vector table address the image is linked for
+    pub load_address: u32,
+}
+
+impl ImageHeader {
+    pub const SIZE: usize = 24;
+
+    pub fn parse<E: CrcEngine<Word = u32>>(
+        bytes: &[u8],
+        crc: &mut Crc32Hasher<E>,
+    ) -> Result<Self, Error> {
+        if bytes.len() < Self::SIZE {
+            return Err(Error::Size);
+        }
+
+        let word = |index: usize| {
+            u32::from_le_bytes([
+                bytes[index * 4],
+                bytes[index * 4 + 1],
+                bytes[index * 4 + 2],
+                bytes[index * 4 + 3],
+            ])
+        };
+
+        if word(0) != IMAGE_MAGIC {
+            return Err(Error::Magic);
+        }
+
+        if crc.reset().update(&bytes[..Self::SIZE - 4]).checksum() != word(5) {
+            return Err(Error::HeaderChecksum);
+        }
+
+        Ok(ImageHeader {
+            version: word(1),
+            size: word(2),
+            checksum: word(3),
+            load_address: word(4),
+        })
+    }
+
+    /// Serialize the header (image packaging)
+    pub fn to_bytes<E: CrcEngine<Word = u32>>(&self, crc: &mut Crc32Hasher<E>) -> [u8; 24] {
+        let mut bytes = [0; Self::SIZE];
+
+        for (index, word) in [
+            IMAGE_MAGIC,
+            self.version,
+            self.size,
+            self.checksum,
+            self.load_address,
+        ]
+        .iter()
+        .enumerate()
+        {
+            bytes[index * 4..index * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+
+        let checksum = crc.reset().update(&bytes[..Self::SIZE - 4]).checksum();
+        bytes[Self::SIZE - 4..].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    /// Check that the image is linked for `slot` and fits in it
+    pub fn check(&self, slot: Slot) -> Result<(), Error> {
+        if self.load_address != slot.vector_table() {
+            Err(Error::LoadAddress)
+        } else if self.size == 0 || self.size > SLOT_SIZE - HEADER_AREA_SIZE {
+            Err(Error::Size)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Header and flags of a slot
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SlotStatus {
+    pub header: ImageHeader,
+    pub ready: bool,
+    pub revoked: bool,
+}
+
+impl SlotStatus {
+    /// Parse the first 64 bytes of a slot
+    pub fn parse<E: CrcEngine<Word = u32>>(
+        bytes: &[u8],
+        crc: &mut Crc32Hasher<E>,
+    ) -> Result<Self, Error> {
+        if bytes.len() < FLAGS_END {
+            return Err(Error::Size);
+        }
+
+        let flag = |offset: usize| {
+            u32::from_le_bytes([
+                bytes[offset],
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+            ])
+        };
+
+        Ok(SlotStatus {
+            header: ImageHeader::parse(bytes, crc)?,
+            ready: flag(READY_OFFSET) == READY,
+            revoked: flag(REVOKED_OFFSET) != ERASED_WORD,
+        })
+    }
+
+    #[inline]
+    pub fn bootable(&self) -> bool {
+        self.ready && !self.revoked
+    }
+}
+
+/// Slot to boot: the bootable one with the highest version, slot A if both have the same
+pub fn select(a: Option<SlotStatus>, b: Option<SlotStatus>) -> Option<Slot> {
+    let a = a.filter(SlotStatus::bootable);
+    let b = b.filter(SlotStatus::bootable);
+
+    match (a, b) {
+        (Some(a), Some(b)) if b.header.version > a.header.version => Some(Slot::B),
+        (Some(_), _) => Some(Slot::A),
+        (None, Some(_)) => Some(Slot::B),
+        (None, None) => None,
+    }
+}
+
+/// Check `image` against the size and the CRC32 of `header`
+pub fn verify_image<E: CrcEngine<Word = u32>>(
+    header: &ImageHeader,
+    image: &[u8],
+    crc: &mut Crc32Hasher<E>,
+) -> Result<(), Error> {
+    if image.len() != header.size as usize {
+        return Err(Error::Size);
+    }
+
+    if crc.reset().update(image).checksum() == header.checksum {
+        Ok(())
+    } else {
+        Err(Error::ImageChecksum)
+    }
+}
+
+/// Start the image of `slot` (bootloader): relocate the vector table and jump to the reset handler
+///
+/// # Safety
+///
+/// The slot must hold a valid image (see `Slot::verify`), and the peripherals used by the
+/// bootloader should be back to their reset state.
+pub unsafe fn boot(slot: Slot) -> ! {
+    let scb = &*SCB::PTR;
+    scb.vtor.write(slot.vector_table());
+    cortex_m::asm::bootload(slot.vector_table() as *const u32)
+}
+
+impl FlashControl<Defined> {
+    /// Unlock and erase `slot` to write a new image, refused for the running slot
+    pub fn start_update<E: CrcEngine<Word = u32>>(
+        &mut self,
+        slot: Slot,
+        crc: E,
+    ) -> Result<Update<'_, E>, Error> {
+        if Slot::running() == Some(slot) {
+            return Err(Error::RunningSlot);
+        }
+
+        let mut flash = self.unlock(slot.address(), SLOT_SIZE)?;
+
+        for address in (slot.address()..slot.address() + SLOT_SIZE).step_by(SECTOR_SIZE as usize) {
+            flash.erase_sector(address)?;
+        }
+
+        Ok(Update {
+            flash,
+            slot,
+            crc: Crc32Hasher::new(crc),
+            offset: 0,
+            buffer: [0xFF; BUFFER_SIZE],
+            buffered: 0,
+        })
+    }
+
+    /// Stop booting `slot`
+    pub fn revoke(&mut self, slot: Slot) -> Result<(), Error> {
+        let mut flash = self.unlock(slot.address(), SECTOR_SIZE)?;
+        flash.program_burst(slot.address() + REVOKED_OFFSET as u32, &flag(REVOKED))?;
+        Ok(())
+    }
+}
+
+/// Image being written to a slot, the slot is locked again when dropped
+pub struct Update<'a, E: CrcEngine<Word = u32>> {
+    flash: Unlocked<'a>,
+    slot: Slot,
+    crc: Crc32Hasher<E>,
+    offset: u32,
+    buffer: [u8; BUFFER_SIZE],
+    buffered: usize,
+}
+
+impl<E: CrcEngine<Word = u32>> Update<'_, E> {
+    #[inline]
+    pub fn slot(&self) -> Slot {
+        self.slot
+    }
+
+    /// Bytes of the file written so far
+    #[inline]
+    pub fn written(&self) -> u32 {
+        self.offset + self.buffered as u32
+    }
+
+    /// Write the next bytes of the file (header area, then image)
+    pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        if self.written() as usize + data.len() > SLOT_SIZE as usize {
+            return Err(Error::Size);
+        }
+
+        for &byte in data {
+            let position = self.written() as usize;
+
+            // The flags are only programmed by `finish` and `revoke`
+            self.buffer[self.buffered] = if (READY_OFFSET..FLAGS_END).contains(&position) {
+                0xFF
+            } else {
+                byte
+            };
+            self.buffered += 1;
+
+            if self.buffered == BUFFER_SIZE {
+                self.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Verify the written image and mark the slot as ready to boot
+    pub fn finish(mut self) -> Result<ImageHeader, Error> {
+        self.flush()?;
+
+        let status = self.slot.status(&mut self.crc)?;
+        status.header.check(self.slot)?;
+
+        match HEADER_AREA_SIZE.checked_add(status.header.size) {
+            Some(end) if self.offset >= end => {}
+            _ => return Err(Error::Size),
+        }
+
+        self.slot.verify(&status.header, &mut self.crc)?;
+        self.flash
+            .program_burst(self.slot.address() + READY_OFFSET as u32, &flag(READY))?;
+
+        Ok(status.header)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        if self.buffered == 0 {
+            return Ok(());
+        }
+
+        let length = (self.buffered as u32).div_ceil(FLASH_WORD_SIZE) * FLASH_WORD_SIZE;
+        self.buffer[self.buffered..].fill(0xFF);
+        self.flash.program_burst(
+            self.slot.address() + self.offset,
+            &self.buffer[..length as usize],
+        )?;
+
+        self.offset += length;
+        self.buffered = 0;
+        Ok(())
+    }
+}
+
+/// Flash word holding `value` in its first 32 bits
+fn flag(value: u32) -> [u8; FLASH_WORD_SIZE as usize] {
+    let mut word = [0xFF; FLASH_WORD_SIZE as usize];
+    word[..4].copy_from_slice(&value.to_le_bytes());
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crc::SoftCrc32;
+
+    fn header(slot: Slot, version: u32, image: &[u8]) -> ImageHeader {
+        ImageHeader {
+            version,
+            size: image.len() as u32,
+            checksum: Crc32Hasher::new(SoftCrc32::new()).update(image).checksum(),
+            load_address: slot.vector_table(),
+        }
+    }
+
+    /// First 64 bytes of a slot holding `header`
+    fn slot_start(header: &ImageHeader, ready: bool, revoked: bool) -> [u8; FLAGS_END] {
+        let mut crc = Crc32Hasher::new(SoftCrc32::new());
+        let mut bytes = [0xFF; FLAGS_END];
+
+        bytes[..ImageHeader::SIZE].copy_from_slice(&header.to_bytes(&mut crc));
+
+        if ready {
+            bytes[READY_OFFSET..READY_OFFSET + 16].copy_from_slice(&flag(READY));
+        }
+
+        if revoked {
+            bytes[REVOKED_OFFSET..REVOKED_OFFSET + 16].copy_from_slice(&flag(REVOKED));
+        }
+
+        bytes
+    }
+
+    fn status(version: u32, ready: bool, revoked: bool) -> Option<SlotStatus> {
+        let header = header(Slot::A, version, b"image");
+        let mut crc = Crc32Hasher::new(SoftCrc32::new());
+        SlotStatus::parse(&slot_start(&header, ready, revoked), &mut crc).ok()
+    }
+
+    #[test]
+    fn header_round_trip() {
+        let mut crc = Crc32Hasher::new(SoftCrc32::new());
+        let header = header(Slot::B, 7, b"image");
+        let bytes = header.to_bytes(&mut crc);
+
+        assert_eq!(ImageHeader::parse(&bytes, &mut crc), Ok(header));
+        assert_eq!(ImageHeader::parse(&bytes[..20], &mut crc), Err(Error::Size));
+
+        let mut corrupted = bytes;
+        corrupted[8] ^= 0x01;
+        assert_eq!(ImageHeader::parse(&corrupted, &mut crc), Err(Error::HeaderChecksum));
+
+        let mut erased = bytes;
+        erased[..4].fill(0xFF);
+        assert_eq!(ImageHeader::parse(&erased, &mut crc), Err(Error::Magic));
+    }
+
+    #[test]
+    fn header_check() {
+        let header = header(Slot::B, 1, b"image");
+
+        assert_eq!(header.check(Slot::B), Ok(()));
+        assert_eq!(header.check(Slot::A), Err(Error::LoadAddress));
+
+        let largest = SLOT_SIZE - HEADER_AREA_SIZE;
+        assert_eq!(ImageHeader { size: largest, ..header }.check(Slot::B), Ok(()));
+
+        for size in [0, largest + 1, u32::MAX] {
+            assert_eq!(ImageHeader { size, ..header }.check(Slot::B), Err(Error::Size));
+        }
+    }
+
+    #[test]
+    fn image_checksum() {
+        let mut crc = Crc32Hasher::new(SoftCrc32::new());
+        let header = header(Slot::A, 1, b"image");
+
+        assert_eq!(verify_image(&header, b"image", &mut crc), Ok(()));
+        assert_eq!(verify_image(&header, b"imagf", &mut crc), Err(Error::ImageChecksum));
+        assert_eq!(verify_image(&header, b"image!", &mut crc), Err(Error::Size));
+    }
+
+    #[test]
+    fn slot_flags() {
+        let ready = status(1, true, false).unwrap();
+        assert!(ready.bootable());
+        assert!(!status(1, false, false).unwrap().bootable());
+        assert!(!status(1, true, true).unwrap().bootable());
+
+        let mut crc = Crc32Hasher::new(SoftCrc32::new());
+        assert_eq!(SlotStatus::parse(&[0xFF; FLAGS_END], &mut crc), Err(Error::Magic));
+    }
+
+    #[test]
+    fn slot_selection() {
+        assert_eq!(select(None, None), None);
+        assert_eq!(select(status(1, true, false), None), Some(Slot::A));
+        assert_eq!(select(None, status(1, true, false)), Some(Slot::B));
+
+        // Highest version, slot A on a tie
+        assert_eq!(select(status(1, true, false), status(2, true, false)), Some(Slot::B));
+        assert_eq!(select(status(3, true, false), status(2, true, false)), Some(Slot::A));
+        assert_eq!(select(status(2, true, false), status(2, true, false)), Some(Slot::A));
+
+        // Only bootable slots
+        assert_eq!(select(status(1, true, false), status(2, false, false)), Some(Slot::A));
+        assert_eq!(select(status(1, true, false), status(2, true, true)), Some(Slot::A));
+        assert_eq!(select(status(1, true, true), status(2, false, false)), None);
+    }
+}
@@ -0,0 +1,327 @@
+//! HAL library for erased GPIO pins - MSP432P401R
+
+/*
+`degrade` turns a pin (`P1_0`, `P2_3`...) into a `Pin<MODE>` holding its port and pin number, and
+`downgrade` into the type of its port (`P1x<MODE>`, `P2x<MODE>`...) holding its pin number. Pins
+of different ports, or of the same port, then share a type: they can be put in an array or passed
+to a function without being generic over every pin.
+
+The registers are addressed from the port and the pin number: `erased_pin!` generates the mode
+conversions, interrupt configuration and embedded-hal traits of the typed pins as well as of the
+erased ones. The port and pin number of a typed pin are constants, the addresses are folded at
+compile time.
+*/
+
+use core::convert::Infallible;
+use core::ptr;
+
+use pac::DIO;
+
+use super::*;
+
+/// 8-bit register offsets of port 1 (port 2 is at +1, ports 3 and 4 at +0x20...)
+pub(super) const REG_IN: usize = 0x00;
+pub(super) const REG_OUT: usize = 0x02;
+pub(super) const REG_DIR: usize = 0x04;
+pub(super) const REG_REN: usize = 0x06;
+pub(super) const REG_DS: usize = 0x08;
+pub(super) const REG_SEL0: usize = 0x0A;
+pub(super) const REG_SEL1: usize = 0x0C;
+pub(super) const REG_IES: usize = 0x18;
+pub(super) const REG_IE: usize = 0x1A;
+pub(super) const REG_IFG: usize = 0x1C;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Port {
+    P1,
+    P2,
+    P3,
+    P4,
+    P5,
+    P6,
+    P7,
+    P8,
+    P9,
+    P10,
+}
+
+impl Port {
+    /// Address of an 8-bit register of the port, `offset` being the one of port 1
+    #[inline]
+    pub(super) fn register(self, offset: usize) -> *mut u8 {
+        let index = self as usize;
+        (DIO::ptr() as usize + (index / 2) * 0x20 + (index % 2) + offset) as *mut u8
+    }
+
+    #[inline]
+    pub(super) fn read(self, offset: usize) -> u8 {
+        unsafe { ptr::read_volatile(self.register(offset)) }
+    }
+
+    #[inline]
+    pub(super) fn write(self, offset: usize, value: u8) {
+        unsafe { ptr::write_volatile(self.register(offset), value) };
+    }
+
+    /// Set (or clear) the `mask` bits of a register
+    #[inline]
+    pub(super) fn set_bits(self, offset: usize, mask: u8, set: bool) {
+        let value = self.read(offset);
+        self.write(offset, if set { value | mask } else { value & !mask });
+    }
+
+    #[inline]
+    pub(super) fn toggle_bits(self, offset: usize, mask: u8) {
+        self.write(offset, self.read(offset) ^ mask);
+    }
+}
+
+/// Mode conversions, interrupt configuration and embedded-hal traits of a pin type
+///
+/// The type provides `port()` and `pin()`, the fields other than `_mode` are listed to rebuild it.
+macro_rules! erased_pin {
+    ($Pin:ident { $($field:ident),* }) => {
+        impl<MODE> $Pin<MODE> {
+            #[inline]
+            fn mask(&self) -> u8 {
+                0x01 << self.pin()
+            }
+
+            /// Setup PullUp resistor and configures Pin to Input mode
+            pub fn into_pull_up_input(self) -> $Pin<Input<PullUp>> {
+                let (port, mask) = (self.port(), self.mask());
+                port.set_bits(REG_DIR, mask, false);
+                port.set_bits(REG_REN, mask, true);
+                port.set_bits(REG_OUT, mask, true);
+                $Pin { $($field: self.$field,)* _mode: Input::_new() }
+            }
+
+            /// Setup PullDown resistor and configures Pin to Input mode
+            pub fn into_pull_down_input(self) -> $Pin<Input<PullDown>> {
+                let (port, mask) = (self.port(), self.mask());
+                port.set_bits(REG_DIR, mask, false);
+                port.set_bits(REG_REN, mask, true);
+                port.set_bits(REG_OUT, mask, false);
+                $Pin { $($field: self.$field,)* _mode: Input::_new() }
+            }
+
+            /// Disables PullUp/PullDown resistor and configures Pin to Input mode
+            pub fn into_floating_input(self) -> $Pin<Input<Floating>> {
+                let (port, mask) = (self.port(), self.mask());
+                port.set_bits(REG_DIR, mask, false);
+                port.set_bits(REG_REN, mask, false);
+                $Pin { $($field: self.$field,)* _mode: Input::_new() }
+            }
+
+            /// Setup Pin to output mode (regular drive strength), the output level (PxOUT) is left as-is
+            pub fn into_output(self) -> $Pin<Output> {
+                let (port, mask) = (self.port(), self.mask());
+                port.set_bits(REG_DS, mask, false);
+                port.set_bits(REG_DIR, mask, true);
+                $Pin { $($field: self.$field,)* _mode: Output::_new() }
+            }
+
+            /// Setup Pin to output mode, driving `state` as soon as the pin is an output
+            pub fn into_push_pull_output_in_state(self, state: PinState) -> $Pin<Output> {
+                self.port().set_bits(REG_OUT, self.mask(), state == PinState::High);
+                self.into_output()
+            }
+
+            /// Setup Pin to open-drain output mode, released (high)
+            pub fn into_open_drain_output(self) -> $Pin<OpenDrain> {
+                self.into_open_drain_output_in_state(PinState::High)
+            }
+
+            /// Setup Pin to open-drain output mode, driven low or released according to `state`
+            pub fn into_open_drain_output_in_state(self, state: PinState) -> $Pin<OpenDrain> {
+                let (port, mask) = (self.port(), self.mask());
+                port.set_bits(REG_REN, mask, false);
+                port.set_bits(REG_DS, mask, false);
+
+                // Never drive the line low when releasing it: release first, then clear PxOUT
+                if state == PinState::High {
+                    port.set_bits(REG_DIR, mask, false);
+                    port.set_bits(REG_OUT, mask, false);
+                } else {
+                    port.set_bits(REG_OUT, mask, false);
+                    port.set_bits(REG_DIR, mask, true);
+                }
+                $Pin { $($field: self.$field,)* _mode: OpenDrain::_new() }
+            }
+
+            /// Setup Primary Module Function
+            pub fn into_alternate_primary(self) -> $Pin<Alternate<Primary>> {
+                let (port, mask) = (self.port(), self.mask());
+                port.set_bits(REG_SEL1, mask, false);
+                port.set_bits(REG_SEL0, mask, true);
+                $Pin { $($field: self.$field,)* _mode: Alternate::_new() }
+            }
+
+            /// Setup Secondary Module Function
+            pub fn into_alternate_secondary(self) -> $Pin<Alternate<Secondary>> {
+                let (port, mask) = (self.port(), self.mask());
+                port.set_bits(REG_SEL1, mask, true);
+                port.set_bits(REG_SEL0, mask, false);
+                $Pin { $($field: self.$field,)* _mode: Alternate::_new() }
+            }
+
+            /// Setup Tertiary Module Function
+            pub fn into_alternate_tertiary(self) -> $Pin<Alternate<Tertiary>> {
+                let (port, mask) = (self.port(), self.mask());
+                port.set_bits(REG_SEL1, mask, true);
+                port.set_bits(REG_SEL0, mask, true);
+                $Pin { $($field: self.$field,)* _mode: Alternate::_new() }
+            }
+        }
+
+        impl<MODE> $Pin<Input<MODE>> {
+            pub fn trigger_on_edge(&mut self, edge: Edge) {
+                let (port, mask) = (self.port(), self.mask());
+
+                // PxIES is also written by the interrupt dispatcher (both edges)
+                cortex_m::interrupt::free(|_| {
+                    port.set_bits(REG_IES, mask, matches!(edge, Edge::Falling))
+                });
+            }
+
+            #[inline]
+            pub fn enable_interrupt(&mut self) {
+                self.port().set_bits(REG_IE, self.mask(), true);
+            }
+
+            #[inline]
+            pub fn disable_interrupt(&mut self) {
+                self.port().set_bits(REG_IE, self.mask(), false);
+            }
+
+            #[inline]
+            pub fn interrupt_enabled(&self) -> bool {
+                self.port().read(REG_IE) & self.mask() != 0
+            }
+
+            #[inline]
+            pub fn clear_interrupt_pending_bit(&mut self) {
+                self.port().set_bits(REG_IFG, self.mask(), false);
+            }
+
+            #[inline]
+            pub fn check_interrupt(&mut self) -> bool {
+                self.port().read(REG_IFG) & self.mask() != 0
+            }
+        }
+
+        impl<MODE> InputPin for $Pin<Input<MODE>> {
+            type Error = Infallible;
+
+            #[inline]
+            fn is_high(&self) -> Result<bool, Self::Error> {
+                self.is_low().map(|low| !low)
+            }
+
+            fn is_low(&self) -> Result<bool, Self::Error> {
+                Ok(self.port().read(REG_IN) & self.mask() == 0)
+            }
+        }
+
+        impl OutputPin for $Pin<Output> {
+            type Error = Infallible;
+
+            fn set_low(&mut self) -> Result<(), Self::Error> {
+                self.port().set_bits(REG_OUT, self.mask(), false);
+                Ok(())
+            }
+
+            fn set_high(&mut self) -> Result<(), Self::Error> {
+                self.port().set_bits(REG_OUT, self.mask(), true);
+                Ok(())
+            }
+        }
+
+        impl StatefulOutputPin for $Pin<Output> {
+            fn is_set_high(&self) -> Result<bool, Self::Error> {
+                self.is_set_low().map(|b| !b)
+            }
+
+            fn is_set_low(&self) -> Result<bool, Self::Error> {
+                Ok(self.port().read(REG_OUT) & self.mask() == 0)
+            }
+        }
+
+        impl ToggleableOutputPin for $Pin<Output> {
+            type Error = Infallible;
+
+            fn toggle(&mut self) -> Result<(), Self::Error> {
+                self.port().toggle_bits(REG_OUT, self.mask());
+                Ok(())
+            }
+        }
+
+        impl InputPin for $Pin<OpenDrain> {
+            type Error = Infallible;
+
+            #[inline]
+            fn is_high(&self) -> Result<bool, Self::Error> {
+                self.is_low().map(|low| !low)
+            }
+
+            fn is_low(&self) -> Result<bool, Self::Error> {
+                Ok(self.port().read(REG_IN) & self.mask() == 0)
+            }
+        }
+
+        impl OutputPin for $Pin<OpenDrain> {
+            type Error = Infallible;
+
+            fn set_low(&mut self) -> Result<(), Self::Error> {
+                self.port().set_bits(REG_DIR, self.mask(), true);
+                Ok(())
+            }
+
+            fn set_high(&mut self) -> Result<(), Self::Error> {
+                self.port().set_bits(REG_DIR, self.mask(), false);
+                Ok(())
+            }
+        }
+
+        impl StatefulOutputPin for $Pin<OpenDrain> {
+            fn is_set_high(&self) -> Result<bool, Self::Error> {
+                self.is_set_low().map(|b| !b)
+            }
+
+            fn is_set_low(&self) -> Result<bool, Self::Error> {
+                Ok(self.port().read(REG_DIR) & self.mask() != 0)
+            }
+        }
+
+        impl ToggleableOutputPin for $Pin<OpenDrain> {
+            type Error = Infallible;
+
+            fn toggle(&mut self) -> Result<(), Self::Error> {
+                self.port().toggle_bits(REG_DIR, self.mask());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Pin of any port
+pub struct Pin<MODE> {
+    pub(super) port: Port,
+    pub(super) i: u8,
+    pub(super) _mode: MODE,
+}
+
+impl<MODE> Pin<MODE> {
+    #[inline]
+    pub fn port(&self) -> Port {
+        self.port
+    }
+
+    /// Pin number in its port (0 to 7)
+    #[inline]
+    pub fn pin(&self) -> u8 {
+        self.i
+    }
+}
+
+erased_pin!(Pin { port, i });
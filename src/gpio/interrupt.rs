@@ -0,0 +1,189 @@
+//! HAL library for GPIO interrupts - MSP432P401R
+
+/*
+Ports 1 to 6 have one interrupt each (PORT1_IRQ to PORT6_IRQ). Reading PxIV returns the pending
+flag with the highest priority (pin 0 first) as 2 * (pin + 1), or 0 when no flag is pending, and
+clears that flag in the same access. `Dispatcher::dispatch` reads it until it returns 0: every
+event is handled once, even when a new one occurs while a handler runs.
+
+For each event the dispatcher records the pin in a bitmap (see `take_events`) and calls the handler
+registered for the pin, if any. Handlers and bitmap are atomics, so the dispatcher can be a
+`static` shared by the application and the interrupt handler without any lock:
+
+    static PORT1: Dispatcher = Dispatcher::new(InterruptPort::Port1);
+
+    #[interrupt]
+    fn PORT1_IRQ() {
+        PORT1.dispatch();
+    }
+
+The pin interrupts are still configured and enabled with `trigger_on_edge` and `enable_interrupt`.
+The pin arguments are pin numbers in the port (0 to 7), the methods panic on a larger number.
+
+-- BOTH EDGES: --
+
+A pin only detects the edge selected in PxIES. For the pins set with `set_both_edges`, the
+dispatcher selects after each event the edge leaving the current level of the pin (falling when it
+is high, rising when it is low), so both edges are reported. Selecting the edge matching the level
+doesn't set PxIFG; an edge occurring between the event and the PxIES update is reported as soon as
+the edge is selected, or missed if the pin is already back to its previous level.
+*/
+
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, AtomicU8, Ordering};
+
+use cortex_m::interrupt;
+use pac::DIO;
+
+use super::{Port, REG_IES, REG_IN};
+
+/// PxIV offset for port 1 (port 2 is at +0x10, ports 3 and 4 at +0x20...)
+const IV_OFFSET: usize = 0x0E;
+
+/// Ports with interrupt capability
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum InterruptPort {
+    Port1,
+    Port2,
+    Port3,
+    Port4,
+    Port5,
+    Port6,
+}
+
+impl From<InterruptPort> for Port {
+    fn from(port: InterruptPort) -> Port {
+        match port {
+            InterruptPort::Port1 => Port::P1,
+            InterruptPort::Port2 => Port::P2,
+            InterruptPort::Port3 => Port::P3,
+            InterruptPort::Port4 => Port::P4,
+            InterruptPort::Port5 => Port::P5,
+            InterruptPort::Port6 => Port::P6,
+        }
+    }
+}
+
+impl InterruptPort {
+    #[inline]
+    fn vector(self) -> *const u16 {
+        let index = self as usize;
+        (DIO::ptr() as usize + (index / 2) * 0x20 + (index % 2) * 0x10 + IV_OFFSET) as *const u16
+    }
+}
+
+/// Interrupt event dispatcher of a port
+pub struct Dispatcher {
+    port: InterruptPort,
+    handlers: [AtomicPtr<()>; 8],
+    events: AtomicU8,
+    both_edges: AtomicU8,
+}
+
+impl Dispatcher {
+    pub const fn new(port: InterruptPort) -> Self {
+        Self {
+            port,
+            handlers: [
+                AtomicPtr::new(ptr::null_mut()),
+                AtomicPtr::new(ptr::null_mut()),
+                AtomicPtr::new(ptr::null_mut()),
+                AtomicPtr::new(ptr::null_mut()),
+                AtomicPtr::new(ptr::null_mut()),
+                AtomicPtr::new(ptr::null_mut()),
+                AtomicPtr::new(ptr::null_mut()),
+                AtomicPtr::new(ptr::null_mut()),
+            ],
+            events: AtomicU8::new(0),
+            both_edges: AtomicU8::new(0),
+        }
+    }
+
+    #[inline]
+    pub fn port(&self) -> InterruptPort {
+        self.port
+    }
+
+    /// Call `handler` with the pin number (0 to 7) on every event of `pin`
+    pub fn set_handler(&self, pin: u8, handler: fn(u8)) {
+        self.handlers[index(pin)].store(handler as *mut (), Ordering::Release);
+    }
+
+    pub fn remove_handler(&self, pin: u8) {
+        self.handlers[index(pin)].store(ptr::null_mut(), Ordering::Release);
+    }
+
+    /// Report both the rising and falling edges of `pin` (or only the edge selected in PxIES)
+    pub fn set_both_edges(&self, pin: u8, enable: bool) {
+        let mask = 1 << index(pin);
+
+        if enable {
+            self.both_edges.fetch_or(mask, Ordering::AcqRel);
+            self.follow_level(pin);
+        } else {
+            self.both_edges.fetch_and(!mask, Ordering::AcqRel);
+        }
+    }
+
+    /// Handle every pending event of the port, to be called from its interrupt handler
+    ///
+    /// Returns the pins having an event.
+    pub fn dispatch(&self) -> u8 {
+        let mut pins = 0;
+
+        loop {
+            let vector = unsafe { ptr::read_volatile(self.port.vector()) };
+            if vector == 0 {
+                break;
+            }
+
+            let pin = (vector / 2 - 1) as u8;
+
+            if self.both_edges.load(Ordering::Acquire) & (1 << pin) != 0 {
+                self.follow_level(pin);
+            }
+
+            pins |= 1 << pin;
+            self.events.fetch_or(1 << pin, Ordering::AcqRel);
+
+            let handler = self.handlers[pin as usize].load(Ordering::Acquire);
+            if !handler.is_null() {
+                let handler: fn(u8) = unsafe { core::mem::transmute(handler) };
+                handler(pin);
+            }
+        }
+
+        pins
+    }
+
+    /// Pins with an event since the last call (bit n is pin n)
+    #[inline]
+    pub fn take_events(&self) -> u8 {
+        self.events.swap(0, Ordering::AcqRel)
+    }
+
+    /// Whether `pin` had an event since the last call
+    #[inline]
+    pub fn take_event(&self, pin: u8) -> bool {
+        let mask = 1 << index(pin);
+        self.events.fetch_and(!mask, Ordering::AcqRel) & mask != 0
+    }
+
+    /// Select the edge leaving the current level of `pin`
+    fn follow_level(&self, pin: u8) {
+        let port = Port::from(self.port);
+
+        // PxIES is shared by the pins of the port, also written by the application
+        interrupt::free(|_| {
+            let high = port.read(REG_IN) & (1 << pin) != 0;
+            port.set_bits(REG_IES, 1 << pin, high);
+        });
+    }
+}
+
+/// Check a pin number
+#[inline]
+fn index(pin: u8) -> usize {
+    assert!(pin < 8, "pin number out of range (0 to 7)");
+    pin as usize
+}
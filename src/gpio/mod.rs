@@ -0,0 +1,424 @@
+#[macro_use]
+mod erased;
+pub mod interrupt;
+mod parallel;
+
+pub use erased::*;
+pub use parallel::*;
+
+use core::marker::PhantomData;
+
+pub use hal::digital::blocking::{InputPin, OutputPin, StatefulOutputPin, ToggleableOutputPin};
+pub use hal::digital::PinState;
+
+pub struct Input<MODE> {
+    _mode: PhantomData<MODE>,
+}
+
+impl<MODE> Input<MODE> {
+    const fn _new() -> Self {
+        Self { _mode: PhantomData }
+    }
+}
+
+pub struct Floating;
+pub struct PullDown;
+pub struct PullUp;
+
+pub struct Output {
+    _mode: PhantomData<()>,
+}
+
+impl Output {
+    const fn _new() -> Self {
+        Self { _mode: PhantomData }
+    }
+}
+
+/// Emulated open-drain output: low is driven (PxDIR set, PxOUT low), high is released (PxDIR
+/// cleared) and pulled up by an external resistor
+pub struct OpenDrain {
+    _mode: PhantomData<()>,
+}
+
+impl OpenDrain {
+    const fn _new() -> Self {
+        Self { _mode: PhantomData }
+    }
+}
+
+pub struct Alternate<MODE> {
+    _mode: PhantomData<MODE>,
+}
+
+impl<MODE> Alternate<MODE> {
+    const fn _new() -> Self {
+        Self { _mode: PhantomData }
+    }
+}
+
+pub struct Primary;
+pub struct Secondary;
+pub struct Tertiary;
+
+/// Output drive strength (PxDS), only selectable on the high drive pins
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DriveStrength {
+    Regular,
+    High,
+}
+
+pub enum Edge {
+  Rising,
+  Falling,
+}
+
+/// Extension trait to split a GPIO peripheral in independent pins and registers
+pub trait GpioExt {
+    /// The to split the GPIO into
+    type Parts;
+
+    /// Splits the GPIO block into independent pins and registers
+    fn split(self) -> Self::Parts;
+}
+
+macro_rules! gpio {
+    ($DIO:ident, {
+        $(($portx:ident, $pxout:ident, $pxin:ident, $PXx:ident): {
+          $($PIx:ident ($port:ident): [
+            $($PI_i:ident: ($pxi:ident, $i:expr, $MODE:ty),)+
+          ])+
+        })+
+    }) => {
+        $(
+            pub mod $portx {
+                use hal::digital::blocking::{OutputPin, InputPin, ToggleableOutputPin};
+                use core::convert::Infallible;
+                use super::*;
+
+                /// Port Group Implementation (PA, PB, PC...), pins of the 16-bit port group
+                pub struct $PXx<MODE, const N: usize> {
+                    mask: u16,
+                    pins: [Pin<MODE>; N],
+                }
+
+                impl<MODE, const N: usize> $PXx<MODE, N> {
+                    /// Group `pins`, all of the ports of the group
+                    pub fn new(pins: [Pin<MODE>; N]) -> Result<Self, ParallelError> {
+                        if N == 0 {
+                            return Err(ParallelError::NoPin);
+                        }
+
+                        let mut mask = 0;
+
+                        for pin in pins.iter() {
+                            if ![$(Port::$port),+].contains(&pin.port) {
+                                return Err(ParallelError::WrongPort);
+                            }
+                            mask |= 0x01 << (pin.i + (pin.port as u8 % 2) * 8);
+                        }
+
+                        Ok(Self { mask, pins })
+                    }
+
+                    /// Pins of the group (bit n is pin n of the first port, bit 8 + n of the second one)
+                    #[inline]
+                    pub fn mask(&self) -> u16 {
+                        self.mask
+                    }
+
+                    /// Input level of the pins (PxIN)
+                    #[inline]
+                    pub fn read(&self) -> u16 {
+                        let dio = unsafe { &*$DIO::ptr() };
+                        dio.$pxin.read().bits() & self.mask
+                    }
+
+                    /// Ungroup the pins
+                    pub fn release(self) -> [Pin<MODE>; N] {
+                        self.pins
+                    }
+                }
+
+                impl<const N: usize> $PXx<Output, N> {
+                    /// Drive the pins of the group to the levels of `value`
+                    #[inline]
+                    pub fn write(&mut self, value: u16) {
+                        self.modify(0xFFFF, value);
+                    }
+
+                    /// Drive the pins of the group selected by `mask` to the levels of `value`
+                    pub fn modify(&mut self, mask: u16, value: u16) {
+                        let dio = unsafe { &*$DIO::ptr() };
+                        let mask = mask & self.mask;
+
+                        if mask == 0xFFFF {
+                            dio.$pxout.write(|w| unsafe { w.bits(value) });
+                        } else {
+                            cortex_m::interrupt::free(|_| {
+                                dio.$pxout.modify(|r, w| unsafe {
+                                    w.bits((r.bits() & !mask) | (value & mask))
+                                });
+                            });
+                        }
+                    }
+
+                    /// Toggle the pins of the group selected by `mask`
+                    pub fn toggle(&mut self, mask: u16) {
+                        let dio = unsafe { &*$DIO::ptr() };
+                        let mask = mask & self.mask;
+
+                        cortex_m::interrupt::free(|_| {
+                            dio.$pxout.modify(|r, w| unsafe { w.bits(r.bits() ^ mask) });
+                        });
+                    }
+
+                    /// Output levels of the pins (PxOUT)
+                    #[inline]
+                    pub fn read_output(&self) -> u16 {
+                        let dio = unsafe { &*$DIO::ptr() };
+                        dio.$pxout.read().bits() & self.mask
+                    }
+                }
+
+                /// Port Implementation (P1, P2, P3..)
+                $(
+                    /// Pin of the port, see `downgrade`
+                    pub struct $PIx<MODE> {
+                        i: u8,
+                        _mode: MODE,
+                    }
+
+                    impl<MODE> $PIx<MODE> {
+                        #[inline]
+                        pub fn port(&self) -> Port {
+                            Port::$port
+                        }
+
+                        /// Pin number in the port (0 to 7)
+                        #[inline]
+                        pub fn pin(&self) -> u8 {
+                            self.i
+                        }
+
+                        /// Erase the port from the type
+                        pub fn degrade(self) -> Pin<MODE> {
+                            Pin { port: Port::$port, i: self.i, _mode: self._mode }
+                        }
+                    }
+
+                    erased_pin!($PIx { i });
+
+                    /// Pin Implementation (P1_1, P1_2...)
+                    $(
+                        pub struct $PI_i<MODE> {
+                            pub(crate) _mode: MODE,
+                        }
+
+                        impl<MODE> $PI_i<MODE> {
+                            #[inline]
+                            pub fn port(&self) -> Port {
+                                Port::$port
+                            }
+
+                            /// Pin number in the port (0 to 7)
+                            #[inline]
+                            pub fn pin(&self) -> u8 {
+                                $i
+                            }
+
+                            /// Erase the port and the pin number from the type
+                            pub fn degrade(self) -> Pin<MODE> {
+                                Pin { port: Port::$port, i: $i, _mode: self._mode }
+                            }
+
+                            /// Erase the pin number from the type
+                            pub fn downgrade(self) -> $PIx<MODE> {
+                                $PIx { i: $i, _mode: self._mode }
+                            }
+                        }
+
+                        erased_pin!($PI_i {});
+                    )+
+                )+
+            }
+        )+
+
+        pub struct Parts {
+            $($($(
+                pub $pxi: $portx::$PI_i<$MODE>,
+            )+)+)+
+        }
+
+        impl GpioExt for $DIO {
+            type Parts = Parts;
+
+            fn split(self) -> Parts {
+                Parts {
+                    $($($(
+                        $pxi: $portx::$PI_i { _mode: <$MODE>::_new() },
+                    )+)+)+
+                }
+            }
+        }
+    }
+}
+
+use pac::DIO;
+
+gpio!(DIO, {
+    (porta, paout, pain, PAx): {
+        P1x (P1): [
+            P1_0: (p1_0, 0, Input<Floating>),
+            P1_1: (p1_1, 1, Input<Floating>),
+            P1_2: (p1_2, 2, Input<Floating>),
+            P1_3: (p1_3, 3, Input<Floating>),
+            P1_4: (p1_4, 4, Input<Floating>),
+            P1_5: (p1_5, 5, Input<Floating>),
+            P1_6: (p1_6, 6, Input<Floating>),
+            P1_7: (p1_7, 7, Input<Floating>),
+        ]
+        P2x (P2): [
+            P2_0: (p2_0, 0, Input<Floating>),
+            P2_1: (p2_1, 1, Input<Floating>),
+            P2_2: (p2_2, 2, Input<Floating>),
+            P2_3: (p2_3, 3, Input<Floating>),
+            P2_4: (p2_4, 4, Input<Floating>),
+            P2_5: (p2_5, 5, Input<Floating>),
+            P2_6: (p2_6, 6, Input<Floating>),
+            P2_7: (p2_7, 7, Input<Floating>),
+        ]
+    }
+    (portb, pbout, pbin, PBx): {
+        P3x (P3): [
+            P3_0: (p3_0, 0, Input<Floating>),
+            P3_1: (p3_1, 1, Input<Floating>),
+            P3_2: (p3_2, 2, Input<Floating>),
+            P3_3: (p3_3, 3, Input<Floating>),
+            P3_4: (p3_4, 4, Input<Floating>),
+            P3_5: (p3_5, 5, Input<Floating>),
+            P3_6: (p3_6, 6, Input<Floating>),
+            P3_7: (p3_7, 7, Input<Floating>),
+        ]
+        P4x (P4): [
+            P4_0: (p4_0, 0, Input<Floating>),
+            P4_1: (p4_1, 1, Input<Floating>),
+            P4_2: (p4_2, 2, Input<Floating>),
+            P4_3: (p4_3, 3, Input<Floating>),
+            P4_4: (p4_4, 4, Input<Floating>),
+            P4_5: (p4_5, 5, Input<Floating>),
+            P4_6: (p4_6, 6, Input<Floating>),
+            P4_7: (p4_7, 7, Input<Floating>),
+        ]
+    }
+    (portc, pcout, pcin, PCx): {
+        P5x (P5): [
+            P5_0: (p5_0, 0, Input<Floating>),
+            P5_1: (p5_1, 1, Input<Floating>),
+            P5_2: (p5_2, 2, Input<Floating>),
+            P5_3: (p5_3, 3, Input<Floating>),
+            P5_4: (p5_4, 4, Input<Floating>),
+            P5_5: (p5_5, 5, Input<Floating>),
+            P5_6: (p5_6, 6, Input<Floating>),
+            P5_7: (p5_7, 7, Input<Floating>),
+        ]
+        P6x (P6): [
+            P6_0: (p6_0, 0, Input<Floating>),
+            P6_1: (p6_1, 1, Input<Floating>),
+            P6_2: (p6_2, 2, Input<Floating>),
+            P6_3: (p6_3, 3, Input<Floating>),
+            P6_4: (p6_4, 4, Input<Floating>),
+            P6_5: (p6_5, 5, Input<Floating>),
+            P6_6: (p6_6, 6, Input<Floating>),
+            P6_7: (p6_7, 7, Input<Floating>),
+        ]
+    }
+    (portd, pdout, pdin, PDx): {
+        P7x (P7): [
+            P7_0: (p7_0, 0, Input<Floating>),
+            P7_1: (p7_1, 1, Input<Floating>),
+            P7_2: (p7_2, 2, Input<Floating>),
+            P7_3: (p7_3, 3, Input<Floating>),
+            P7_4: (p7_4, 4, Input<Floating>),
+            P7_5: (p7_5, 5, Input<Floating>),
+            P7_6: (p7_6, 6, Input<Floating>),
+            P7_7: (p7_7, 7, Input<Floating>),
+        ]
+        P8x (P8): [
+            P8_0: (p8_0, 0, Input<Floating>),
+            P8_1: (p8_1, 1, Input<Floating>),
+            P8_2: (p8_2, 2, Input<Floating>),
+            P8_3: (p8_3, 3, Input<Floating>),
+            P8_4: (p8_4, 4, Input<Floating>),
+            P8_5: (p8_5, 5, Input<Floating>),
+            P8_6: (p8_6, 6, Input<Floating>),
+            P8_7: (p8_7, 7, Input<Floating>),
+        ]
+    }
+    (porte, peout, pein, PEx): {
+        P9x (P9): [
+            P9_0: (p9_0, 0, Input<Floating>),
+            P9_1: (p9_1, 1, Input<Floating>),
+            P9_2: (p9_2, 2, Input<Floating>),
+            P9_3: (p9_3, 3, Input<Floating>),
+            P9_4: (p9_4, 4, Input<Floating>),
+            P9_5: (p9_5, 5, Input<Floating>),
+            P9_6: (p9_6, 6, Input<Floating>),
+            P9_7: (p9_7, 7, Input<Floating>),
+        ]
+        P10x (P10): [
+            P10_0: (p10_0, 0, Input<Floating>),
+            P10_1: (p10_1, 1, Input<Floating>),
+            P10_2: (p10_2, 2, Input<Floating>),
+            P10_3: (p10_3, 3, Input<Floating>),
+            P10_4: (p10_4, 4, Input<Floating>),
+            P10_5: (p10_5, 5, Input<Floating>),
+            P10_6: (p10_6, 6, Input<Floating>),
+            P10_7: (p10_7, 7, Input<Floating>),
+        ]
+    }
+});
+
+/// Pins with a high drive strength option (P2.0 - P2.3)
+macro_rules! high_drive {
+    ($portx:ident, $pxds:ident, $pids:ident, [$($PI_i:ident: $i:expr,)+]) => {
+        $(
+            impl<MODE> $portx::$PI_i<MODE> {
+                /// Setup Pin to output mode with the high drive strength
+                pub fn into_output_high_drive(self) -> $portx::$PI_i<Output> {
+                    let mut pin = self.into_output();
+                    pin.set_drive_strength(DriveStrength::High);
+                    pin
+                }
+            }
+
+            impl $portx::$PI_i<Output> {
+                pub fn set_drive_strength(&mut self, strength: DriveStrength) {
+                    let dio = unsafe { &*DIO::ptr() };
+                    dio.$pxds.modify(|r,w| unsafe {
+                        match strength {
+                            DriveStrength::Regular => w.$pids().bits(r.$pids().bits() & !(0x01 << $i)),
+                            DriveStrength::High => w.$pids().bits(r.$pids().bits() | (0x01 << $i)),
+                        }
+                    });
+                }
+
+                pub fn drive_strength(&self) -> DriveStrength {
+                    let dio = unsafe { &*DIO::ptr() };
+                    if dio.$pxds.read().$pids().bits() & (0x01 << $i) != 0 {
+                        DriveStrength::High
+                    } else {
+                        DriveStrength::Regular
+                    }
+                }
+            }
+        )+
+    }
+}
+
+high_drive!(porta, pads, p2ds, [
+    P2_0: 0,
+    P2_1: 1,
+    P2_2: 2,
+    P2_3: 3,
+]);
@@ -0,0 +1,106 @@
+//! HAL library for parallel GPIO port access - MSP432P401R
+
+/*
+`Port8` groups pins of one 8-bit port (P1 to P10) and the port group types (`PAx`, `PBx`... see
+`gpio!`) pins of one 16-bit port group (PA is P1 in the low byte and P2 in the high byte, PB is P3
+and P4...). They are built from erased pins (see `degrade`) and own them until `release`, so no
+other code can drive the pins of the group in the meantime.
+
+Bit n of the values is pin n of the port (bit 8 + n is pin n of the second port of a group), the
+bits of pins outside the group are ignored. The pins are read in a single access (PxIN). They are
+written in a single access (PxOUT) as well: when the group doesn't hold every pin of the port, the
+read-modify-write of PxOUT runs with the interrupts disabled, so the other pins of the port can be
+driven from an interrupt handler.
+*/
+
+use cortex_m::interrupt;
+
+use super::*;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ParallelError {
+    /// No pin to group
+    NoPin,
+    /// The pins are not all in the port (or port group)
+    WrongPort,
+}
+
+/// Pins of an 8-bit port
+pub struct Port8<MODE, const N: usize> {
+    port: Port,
+    mask: u8,
+    pins: [Pin<MODE>; N],
+}
+
+impl<MODE, const N: usize> Port8<MODE, N> {
+    /// Group `pins`, all of the same port
+    pub fn new(pins: [Pin<MODE>; N]) -> Result<Self, ParallelError> {
+        let port = pins.first().ok_or(ParallelError::NoPin)?.port;
+        let mut mask = 0;
+
+        for pin in pins.iter() {
+            if pin.port != port {
+                return Err(ParallelError::WrongPort);
+            }
+            mask |= 0x01 << pin.i;
+        }
+
+        Ok(Self { port, mask, pins })
+    }
+
+    #[inline]
+    pub fn port(&self) -> Port {
+        self.port
+    }
+
+    /// Pins of the group (bit n is pin n)
+    #[inline]
+    pub fn mask(&self) -> u8 {
+        self.mask
+    }
+
+    /// Input level of the pins (PxIN)
+    #[inline]
+    pub fn read(&self) -> u8 {
+        self.port.read(REG_IN) & self.mask
+    }
+
+    /// Ungroup the pins
+    pub fn release(self) -> [Pin<MODE>; N] {
+        self.pins
+    }
+}
+
+impl<const N: usize> Port8<Output, N> {
+    /// Drive the pins of the group to the levels of `value`
+    #[inline]
+    pub fn write(&mut self, value: u8) {
+        self.modify(0xFF, value);
+    }
+
+    /// Drive the pins of the group selected by `mask` to the levels of `value`
+    pub fn modify(&mut self, mask: u8, value: u8) {
+        let mask = mask & self.mask;
+
+        if mask == 0xFF {
+            self.port.write(REG_OUT, value);
+        } else {
+            interrupt::free(|_| {
+                let out = self.port.read(REG_OUT);
+                self.port.write(REG_OUT, (out & !mask) | (value & mask));
+            });
+        }
+    }
+
+    /// Toggle the pins of the group selected by `mask`
+    pub fn toggle(&mut self, mask: u8) {
+        let mask = mask & self.mask;
+        interrupt::free(|_| self.port.toggle_bits(REG_OUT, mask));
+    }
+
+    /// Output levels of the pins (PxOUT)
+    #[inline]
+    pub fn read_output(&self) -> u8 {
+        self.port.read(REG_OUT) & self.mask
+    }
+}
@@ -1,14 +1,23 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
 extern crate embedded_hal as hal;
 extern crate msp432p401r as pac;
 
+pub mod aes;
 pub mod clock;
+pub mod crc;
 pub mod flash;
 pub mod gpio;
 pub mod pcm;
 pub mod pmap;
+pub mod pss;
+pub mod reset;
+pub mod rng;
+pub mod rtc;
 pub mod serial;
+pub mod sysctl;
+pub mod system;
 pub mod time;
 pub mod timer;
+pub mod tlv;
 pub mod watchdog;
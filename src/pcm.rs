@@ -25,13 +25,147 @@ LPM3 --> Deep Sleep, only LDO voltage options, max 32,768 kHz frequency;
 LPM4 --> Deep Sleep, only LDO voltage options, with RTC and WDT modules disabled;
 LPMx.5 --> Lowest power consumption
 
+-- LOW POWER MODE ENTRY: --
+
+LPM0 --> From any active mode, wakes up on any enabled interrupt;
+LPM3 --> From AM_LDO_VCOREx, ACLK and BCLK up to 32.768 kHz, wakes up on RTC, WDT or port interrupts;
+LPM4 --> LPM3 with the RTC and the WDT halted, wakes up on port interrupts;
+LPM3.5 --> From AM_LDO_VCORE0, wakes up (through a reset) on RTC or P1 - P6 interrupts;
+LPM4.5 --> From AM_LDO_VCOREx, wakes up (through a reset) on P1 - P6 interrupts;
+
+A peripheral still requesting a clock that is not available in the requested low power mode
+blocks the entry (LPM_INVALID_CLK), as does an illegal transition (LPM_INVALID_TR): the device
+stays in LPM0 and the flag tells what went wrong. With FORCE_LPM_ENTRY set, the entry happens
+anyway and the clock requests are ignored (the peripherals stop working until wake-up).
+
+-- LPMx.5 WAKE-UP: --
+
+Waking up from LPM3.5 or LPM4.5 resets the device, but the I/O pins keep their state (LOCKLPM5)
+and, after LPM3.5, the RTC keeps running with its configuration locked (LOCKBKUP). The pins must be
+configured again (direction, output, interrupts) to the retained state before releasing the lock,
+otherwise they glitch to their reset state. The wake-up interrupt flag stays set until cleared.
+
 */
 
-use pac::PCM;
+use pac::{Interrupt, DIO, PCM, RSTCTL, RTC_C, WDT_A};
 use core::arch::asm;
 use cortex_m::interrupt;
+use cortex_m::peripheral::{NVIC, SCB};
+
+use crate::clock::{Clocks, LFXTCLK};
+
+const VCORE0_MAX_MCLK: u32 = 24_000_000;
+const VCORE0_MAX_SMCLK: u32 = 12_000_000;
+const VCORE1_MAX_MCLK: u32 = 48_000_000;
+const VCORE1_MAX_SMCLK: u32 = 24_000_000;
+const LF_MAX_CLK: u32 = 128_000;
+
+const LPMR_MASK: u16 = 0xFF0F;
+const LPMR_LPM3: u16 = 0x00;
+const LPMR_LPM35: u16 = 0xA0;
+const LPMR_LPM45: u16 = 0xC0;
+
+const SCR_SLEEPDEEP: u32 = 0x04;
+
+const MAX_TRANSITIONS: u8 = 4;
+const PMR_BUSY_TIMEOUT: u32 = 100_000;
+
+const LPM_INVALID_TRANSITION: u32 = 0x01;
+const LPM_INVALID_CLOCK: u32 = 0x02;
+const AM_INVALID_TRANSITION: u32 = 0x04;
+const DCDC_ERROR: u32 = 0x40;
+const AM_FLAGS: u32 = AM_INVALID_TRANSITION | DCDC_ERROR;
+const LPM_FLAGS: u32 = LPM_INVALID_TRANSITION | LPM_INVALID_CLOCK;
+const ALL_FLAGS: u32 = LPM_FLAGS | AM_FLAGS;
+
+const PCM_KEY: u32 = 0x695A_0000;
+const PCM_KEY_MASK: u32 = 0xFFFF_0000;
+const LOCK_LPM5: u32 = 0x01;
+const LOCK_BACKUP: u32 = 0x02;
+const FORCE_LPM_ENTRY: u32 = 0x04;
+const RESET_LPM35: u32 = 0x01;
+const RESET_LPM45: u32 = 0x02;
+const RTC_FLAGS: u16 = 0x000F;
+const RTC_PRESCALER_FLAG: u16 = 0x0001;
+const WAKE_PORTS: u8 = 6;
+
+const RTC_HOLD: u16 = 0x0040;
+const RTC_INTERRUPTS: u16 = 0x00F0;
+const RTC_PRESCALER_INTERRUPT: u16 = 0x0002;
+const WDT_HOLD: u16 = 0x0080;
+
+const TIMER_WAKE_SOURCES: [Interrupt; 2] = [Interrupt::RTC_C_IRQ, Interrupt::WDT_A_IRQ];
+const PORT_WAKE_SOURCES: [Interrupt; 6] = [
+    Interrupt::PORT1_IRQ,
+    Interrupt::PORT2_IRQ,
+    Interrupt::PORT3_IRQ,
+    Interrupt::PORT4_IRQ,
+    Interrupt::PORT5_IRQ,
+    Interrupt::PORT6_IRQ,
+];
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Lpmx5 {
+    Lpm35,
+    Lpm45,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WakeSource {
+    /// Port interrupt, lowest pending pin (e.g. P1.4 -> `Port { port: 1, pin: 4 }`)
+    Port { port: u8, pin: u8 },
+    /// RTC interrupt (LPM3.5 only)
+    Rtc,
+    /// No wake-up flag set (e.g. already cleared)
+    Unknown,
+}
+
+/// Details of a LPMx.5 wake-up
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Lpmx5Wakeup {
+    pub mode: Lpmx5,
+    pub source: WakeSource,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// A clock is too fast for the current or the requested power mode
+    ClockFrequency,
+    /// The requested low power mode can't be entered from the current active mode
+    InvalidPowerMode,
+    /// The RTC or the WDT is running (LPM4 and LPM4.5 halt them)
+    PeripheralRunning,
+    /// Nothing enabled can wake the device up from the requested low power mode
+    NoWakeSource,
+    /// The device did not enter LPMx.5 (e.g. an interrupt was pending)
+    EntryAborted,
+    /// The PCM did not complete the power mode request in time
+    Timeout,
+    /// Invalid active mode transition requested (AM_INVALID_TR)
+    InvalidActiveTransition,
+    /// Invalid low power mode transition requested (LPM_INVALID_TR)
+    InvalidLpmTransition,
+    /// A peripheral clock request blocked the low power mode entry (LPM_INVALID_CLK)
+    InvalidLpmClock,
+    /// The DC-DC regulator failed, the PCM fell back to the LDO (DCDC_ERROR)
+    DcdcFailure,
+}
+
+impl Error {
+    const fn flag(&self) -> u32 {
+        match *self {
+            Error::InvalidActiveTransition => AM_INVALID_TRANSITION,
+            Error::InvalidLpmTransition => LPM_INVALID_TRANSITION,
+            Error::InvalidLpmClock => LPM_INVALID_CLOCK,
+            Error::DcdcFailure => DCDC_ERROR,
+            _ => 0,
+        }
+    }
+}
 
+#[derive(Debug)]
 pub struct PcmNotDefined;
+#[derive(Debug)]
 pub struct PcmDefined;
 
 pub trait State {}
@@ -59,7 +193,60 @@ pub enum VCoreSel {
     LfVcore1,
 }
 
+/// Core voltage regulator used in active mode
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Regulator {
+    Ldo,
+    Dcdc,
+}
+
 impl VCoreSel {
+    /// Lowest power active mode able to run `clocks` with `regulator` (LF modes excluded)
+    pub fn select(clocks: &Clocks, regulator: Regulator) -> Option<VCoreSel> {
+        let candidates = match regulator {
+            Regulator::Ldo => [VCoreSel::LdoVcore0, VCoreSel::LdoVcore1],
+            Regulator::Dcdc => [VCoreSel::DcdcVcore0, VCoreSel::DcdcVcore1],
+        };
+
+        candidates.iter().copied().find(|mode| mode.supports(clocks))
+    }
+
+    /// LF active mode if `clocks` are slow enough for it (up to 128 kHz)
+    ///
+    /// The LF modes also require every clock to be sourced from LFXT, VLO or REFO, which
+    /// `clocks` can't tell: a DCO derived 128 kHz clock makes the transition fail.
+    pub fn select_low_frequency(clocks: &Clocks) -> Option<VCoreSel> {
+        Some(VCoreSel::LfVcore0).filter(|mode| mode.supports(clocks))
+    }
+
+    /// Highest MCLK/HSMCLK and SMCLK frequencies allowed in this active mode
+    pub const fn max_frequencies(&self) -> (u32, u32) {
+        match *self {
+            VCoreSel::LfVcore0 | VCoreSel::LfVcore1 => (LF_MAX_CLK, LF_MAX_CLK),
+            VCoreSel::LdoVcore1 | VCoreSel::DcdcVcore1 => (VCORE1_MAX_MCLK, VCORE1_MAX_SMCLK),
+            VCoreSel::LdoVcore0 | VCoreSel::DcdcVcore0 => (VCORE0_MAX_MCLK, VCORE0_MAX_SMCLK),
+        }
+    }
+
+    pub fn supports(&self, clocks: &Clocks) -> bool {
+        let (max_mclk, max_smclk) = self.max_frequencies();
+        clocks.mclk.0 <= max_mclk && clocks.hsmclk.0 <= max_mclk && clocks.smclk.0 <= max_smclk
+    }
+
+    /// LDO mode at the same core voltage
+    pub const fn ldo(&self) -> VCoreSel {
+        if self.is_vcore1() {
+            VCoreSel::LdoVcore1
+        } else {
+            VCoreSel::LdoVcore0
+        }
+    }
+
+    #[inline]
+    pub const fn is_vcore1(&self) -> bool {
+        matches!(*self, VCoreSel::LdoVcore1 | VCoreSel::DcdcVcore1 | VCoreSel::LfVcore1)
+    }
+
     fn vcoresel(&self) -> u16 {
         match *self {
             VCoreSel::LdoVcore0 => 0x00,
@@ -89,6 +276,7 @@ pub enum VCoreCheck {
     Lpm3,
 }
 
+#[derive(Debug)]
 pub struct PcmConfig <S: State>{
     pcm: PCM,
     source: VCoreSel,
@@ -124,11 +312,59 @@ impl <S>PcmConfig<S> where S: State{
         make_pcmconf!(self, PcmDefined)
     }
 
+    /// Wake-up details if the device just came back from LPM3.5 or LPM4.5
+    pub fn lpmx5_wakeup(&self) -> Option<Lpmx5Wakeup> {
+        if !self.is_io_locked() {
+            return None;
+        }
+
+        // Read-only access, the flags are owned (and cleared) by `reset::ResetControl`
+        let status = unsafe { (*RSTCTL::ptr()).rstctl_pcmreset_stat.read().bits() };
+
+        let mode = if status & RESET_LPM35 != 0 {
+            Lpmx5::Lpm35
+        } else if status & RESET_LPM45 != 0 {
+            Lpmx5::Lpm45
+        } else {
+            return None;
+        };
+
+        let source = match port_wake_source() {
+            Some(source) => source,
+            None if mode == Lpmx5::Lpm35 && rtc_flag_set() => WakeSource::Rtc,
+            None => WakeSource::Unknown,
+        };
+
+        Some(Lpmx5Wakeup { mode, source })
+    }
+
+    /// The I/O pins (and the backup domain) still hold their LPMx.5 state
+    #[inline]
+    pub fn is_io_locked(&self) -> bool {
+        self.pcm.pcmctl1.read().bits() & (LOCK_LPM5 | LOCK_BACKUP) != 0
+    }
+
+    /// Run `configure` (restore the pins to their retained state) and then release the I/O lock
+    pub fn unlock_io<R, F: FnOnce() -> R>(&mut self, configure: F) -> R {
+        let result = configure();
+
+        self.pcm.pcmctl1.modify(|r, w| unsafe {
+            w.bits((r.bits() & !(PCM_KEY_MASK | LOCK_LPM5 | LOCK_BACKUP)) | PCM_KEY)
+        });
+
+        result
+    }
 }
 
 impl PcmConfig<PcmDefined> {
 
-    pub fn freeze(mut self) -> Self {
+    /// Apply the active mode request, stepping through the intermediate modes
+    ///
+    /// The configuration is handed back with the error, so the request can be retried. If the
+    /// DC-DC regulator fails to start the PCM falls back to the LDO at the same core voltage: the
+    /// error is `Error::DcdcFailure`, the configuration requests that LDO mode and `check_errors`
+    /// reports the failure until `clear_errors` is called.
+    pub fn freeze(mut self) -> Result<Self, (Self, Error)> {
 
         let mut source_state: VCoreSel;
         source_state = self.get_vcore();
@@ -137,10 +373,10 @@ impl PcmConfig<PcmDefined> {
             for _n in 1..50 {
                 unsafe{asm!("NOP")};
             }
-            return self;
+            return Ok(self);
         }
 
-        while source_state != self.source {
+        for _n in 0..MAX_TRANSITIONS {
 
             if source_state == VCoreSel::DcdcVcore1 ||
                 source_state == VCoreSel::LfVcore1 {
@@ -158,23 +394,46 @@ impl PcmConfig<PcmDefined> {
                 source_state = self.source;
             }
 
-            self.set_vcore_inline(source_state);
+            if let Err(error) = self.set_vcore_inline(source_state, AM_FLAGS) {
+                if error != Error::DcdcFailure {
+                    return Err((self, error));
+                }
+
+                // The PCM is back on the LDO, make it the request too (keeping DCDC_ERROR set)
+                self.source = source_state.ldo();
+
+                return match self.set_vcore_inline(self.source, AM_INVALID_TRANSITION) {
+                    Ok(()) => Err((self, Error::DcdcFailure)),
+                    Err(error) => Err((self, error)),
+                };
+            }
+
             source_state = self.get_vcore();
 
+            if source_state == self.source {
+                return Ok(self);
+            }
         }
 
-        self.source = source_state;
-        self
+        Err((self, Error::Timeout))
     }
 
-    fn wait_pcm(&self) {
+    fn wait_pcm(&self) -> Result<(), Error> {
+        let mut attempts: u32 = 0;
+
         while (self.pcm.pcmctl1.read().bits() >> 8) & 0x01 != 0 {
+            attempts += 1;
+            if attempts >= PMR_BUSY_TIMEOUT {
+                return Err(Error::Timeout);
+            }
             unsafe{asm!("NOP")};
         }
 
         for _n in 1..50 {
             unsafe{asm!("NOP")};
         }
+
+        Ok(())
     }
 
     fn set_reg_mask(&self, value: u16, mask: u16) {
@@ -190,15 +449,59 @@ impl PcmConfig<PcmDefined> {
         });
     }
 
-    fn set_vcore_inline(&mut self, source: VCoreSel) {
+    /// Request `source`, `flags` are cleared before and checked after the transition
+    fn set_vcore_inline(&mut self, source: VCoreSel, flags: u32) -> Result<(), Error> {
 
         let amr_mask: u16 = 0xFFF0;
 
         interrupt::free(|_| {
-            self.wait_pcm();
+            self.wait_pcm()?;
+            self.pcm.pcmclrifg.write(|w| unsafe { w.bits(flags) });
             self.set_reg_mask(source.vcoresel(), amr_mask);
-            self.wait_pcm();
+            self.wait_pcm()?;
+            self.check_flags(flags)
+        })
+    }
+
+    /// Pending PCM error flags, the most severe first
+    pub fn check_errors(&self) -> Result<(), Error> {
+        self.check_flags(ALL_FLAGS)
+    }
+
+    #[inline]
+    pub fn clear_errors(&mut self) -> &mut Self {
+        self.pcm.pcmclrifg.write(|w| unsafe { w.bits(ALL_FLAGS) });
+        self
+    }
+
+    /// Enable (or not) the PCM interrupt for `error` (`Timeout` is ignored)
+    pub fn enable_interrupt(&mut self, error: Error, enable: bool) -> &mut Self {
+        let flag = error.flag();
+
+        self.pcm.pcmie.modify(|r, w| unsafe {
+            if enable {
+                w.bits(r.bits() | flag)
+            } else {
+                w.bits(r.bits() & !flag)
+            }
         });
+        self
+    }
+
+    fn check_flags(&self, mask: u32) -> Result<(), Error> {
+        let flags = self.pcm.pcmifg.read().bits() & mask;
+
+        if flags & AM_INVALID_TRANSITION != 0 {
+            Err(Error::InvalidActiveTransition)
+        } else if flags & DCDC_ERROR != 0 {
+            Err(Error::DcdcFailure)
+        } else if flags & LPM_INVALID_TRANSITION != 0 {
+            Err(Error::InvalidLpmTransition)
+        } else if flags & LPM_INVALID_CLOCK != 0 {
+            Err(Error::InvalidLpmClock)
+        } else {
+            Ok(())
+        }
     }
 
     fn get_vcore(&self) -> VCoreSel {
@@ -232,6 +535,206 @@ impl PcmConfig<PcmDefined> {
             _ => VCoreCheck::LdoVcore0,
         }
     }
+
+    /// Enter LPM0 (Sleep) until an interrupt occurs
+    pub fn sleep_lpm0(&mut self, clocks: &Clocks) -> Result<(), Error> {
+        self.check_active_clocks(clocks)?;
+
+        if !any_interrupt_enabled() {
+            return Err(Error::NoWakeSource);
+        }
+
+        sleep(false);
+        Ok(())
+    }
+
+    /// Enter LPM3 (Deep Sleep) until a RTC, WDT or port interrupt occurs
+    pub fn deep_sleep_lpm3(&mut self, clocks: &Clocks) -> Result<(), Error> {
+        self.check_ldo()?;
+
+        if clocks.aclk.0 > LFXTCLK || clocks.bclk.0 > LFXTCLK {
+            return Err(Error::ClockFrequency);
+        }
+
+        if !any_enabled(&TIMER_WAKE_SOURCES) && !any_enabled(&PORT_WAKE_SOURCES) {
+            return Err(Error::NoWakeSource);
+        }
+
+        self.deep_sleep(LPMR_LPM3)
+    }
+
+    /// Enter LPM4 (LPM3 with every clock off) until a port interrupt occurs
+    pub fn lpm4(&mut self) -> Result<(), Error> {
+        self.check_ldo()?;
+
+        if !rtc_wdt_halted() {
+            return Err(Error::PeripheralRunning);
+        }
+
+        if !any_enabled(&PORT_WAKE_SOURCES) {
+            return Err(Error::NoWakeSource);
+        }
+
+        self.deep_sleep(LPMR_LPM3)
+    }
+
+    /// Enter LPM3.5, the device wakes up through a reset on a RTC or P1 - P6 interrupt
+    pub fn lpm35(&mut self, clocks: &Clocks) -> Result<(), Error> {
+        if self.get_powermode() != VCoreCheck::LdoVcore0 {
+            return Err(Error::InvalidPowerMode);
+        }
+
+        if clocks.bclk.0 > LFXTCLK {
+            return Err(Error::ClockFrequency);
+        }
+
+        if !rtc_interrupt_enabled() && !port_interrupt_enabled() {
+            return Err(Error::NoWakeSource);
+        }
+
+        self.deep_sleep(LPMR_LPM35)?;
+        Err(Error::EntryAborted)
+    }
+
+    /// Enter LPM4.5, the device wakes up through a reset on a P1 - P6 interrupt
+    pub fn lpm45(&mut self) -> Result<(), Error> {
+        self.check_ldo()?;
+
+        if !port_interrupt_enabled() {
+            return Err(Error::NoWakeSource);
+        }
+
+        self.deep_sleep(LPMR_LPM45)?;
+        Err(Error::EntryAborted)
+    }
+
+    /// Ignore the peripheral clock requests when entering LPM3, LPM4 or LPMx.5
+    pub fn set_force_lpm_entry(&mut self, force: bool) -> &mut Self {
+        self.pcm.pcmctl1.modify(|r, w| unsafe {
+            let value = r.bits() & !(PCM_KEY_MASK | FORCE_LPM_ENTRY);
+            if force {
+                w.bits(value | FORCE_LPM_ENTRY | PCM_KEY)
+            } else {
+                w.bits(value | PCM_KEY)
+            }
+        });
+        self
+    }
+
+    #[inline]
+    pub fn is_force_lpm_entry(&self) -> bool {
+        self.pcm.pcmctl1.read().bits() & FORCE_LPM_ENTRY != 0
+    }
+
+    /// Reason of the last blocked low power mode entry, if any
+    #[inline]
+    pub fn lpm_violation(&self) -> Option<Error> {
+        self.check_flags(LPM_FLAGS).err()
+    }
+
+    /// Enter the `lpmr` deep sleep mode, failing if the PCM refused the entry
+    fn deep_sleep(&mut self, lpmr: u16) -> Result<(), Error> {
+        self.pcm.pcmclrifg.write(|w| unsafe { w.bits(LPM_FLAGS) });
+        self.set_reg_mask(lpmr, LPMR_MASK);
+        sleep(true);
+        self.check_flags(LPM_FLAGS)
+    }
+
+    fn check_ldo(&self) -> Result<(), Error> {
+        match self.get_powermode() {
+            VCoreCheck::LdoVcore0 | VCoreCheck::LdoVcore1 => Ok(()),
+            _ => Err(Error::InvalidPowerMode),
+        }
+    }
+
+    fn check_active_clocks(&self, clocks: &Clocks) -> Result<(), Error> {
+        let mode = match self.get_powermode() {
+            VCoreCheck::LfVcore0 | VCoreCheck::Lpm0LfVcore0 => VCoreSel::LfVcore0,
+            VCoreCheck::LfVcore1 | VCoreCheck::Lpm0LfVcore1 => VCoreSel::LfVcore1,
+            VCoreCheck::LdoVcore1 | VCoreCheck::Lpm0LdoVcore1 => VCoreSel::LdoVcore1,
+            VCoreCheck::DcdcVcore1 | VCoreCheck::Lpm0DcdcVcore1 => VCoreSel::DcdcVcore1,
+            _ => VCoreSel::LdoVcore0,
+        };
+
+        if !mode.supports(clocks) {
+            return Err(Error::ClockFrequency);
+        }
+
+        Ok(())
+    }
+}
+
+fn sleep(deep: bool) {
+    // SCB is owned by cortex_m::Peripherals, only SCR.SLEEPDEEP is touched here
+    let scb = unsafe { &*SCB::PTR };
+
+    unsafe {
+        if deep {
+            scb.scr.modify(|r| r | SCR_SLEEPDEEP);
+        } else {
+            scb.scr.modify(|r| r & !SCR_SLEEPDEEP);
+        }
+    }
+
+    cortex_m::asm::dsb();
+    cortex_m::asm::wfi();
+
+    unsafe { scb.scr.modify(|r| r & !SCR_SLEEPDEEP) };
+}
+
+fn any_interrupt_enabled() -> bool {
+    let nvic = unsafe { &*NVIC::PTR };
+    nvic.iser.iter().any(|iser| iser.read() != 0)
+}
+
+fn any_enabled(sources: &[Interrupt]) -> bool {
+    sources.iter().any(|&source| NVIC::is_enabled(source))
+}
+
+fn rtc_wdt_halted() -> bool {
+    let rtc = unsafe { &*RTC_C::ptr() };
+    let wdt = unsafe { &*WDT_A::ptr() };
+
+    rtc.rtcctl13.read().bits() & RTC_HOLD != 0 && wdt.wdtctl.read().bits() & WDT_HOLD != 0
+}
+
+fn rtc_interrupt_enabled() -> bool {
+    let rtc = unsafe { &*RTC_C::ptr() };
+
+    rtc.rtcctl13.read().bits() & RTC_HOLD == 0
+        && (rtc.rtcctl0.read().bits() & RTC_INTERRUPTS != 0
+            || rtc.rtcps0ctl.read().bits() & RTC_PRESCALER_INTERRUPT != 0
+            || rtc.rtcps1ctl.read().bits() & RTC_PRESCALER_INTERRUPT != 0)
+}
+
+fn port_wake_source() -> Option<WakeSource> {
+    let dio = unsafe { &*DIO::ptr() };
+    let flags = [dio.paifg.read().bits(), dio.pbifg.read().bits(), dio.pcifg.read().bits()];
+
+    (0..WAKE_PORTS).find_map(|port| {
+        let flag = (flags[(port / 2) as usize] >> (8 * (port % 2))) as u8;
+
+        if flag == 0 {
+            None
+        } else {
+            Some(WakeSource::Port {
+                port: port + 1,
+                pin: flag.trailing_zeros() as u8,
+            })
+        }
+    })
 }
 
+fn rtc_flag_set() -> bool {
+    let rtc = unsafe { &*RTC_C::ptr() };
+
+    rtc.rtcctl0.read().bits() & RTC_FLAGS != 0
+        || rtc.rtcps0ctl.read().bits() & RTC_PRESCALER_FLAG != 0
+        || rtc.rtcps1ctl.read().bits() & RTC_PRESCALER_FLAG != 0
+}
 
+fn port_interrupt_enabled() -> bool {
+    let dio = unsafe { &*DIO::ptr() };
+
+    dio.paie.read().bits() != 0 || dio.pbie.read().bits() != 0 || dio.pcie.read().bits() != 0
+}
@@ -0,0 +1,213 @@
+//! HAL library for PSS (Power Supply System) - MSP432P401R
+
+/*
+- VCCDET --> VCC detector, always on, keeps the device in reset (POR) while VCC is too low;
+- SVSMH --> High-side (VCC) Supply Voltage Supervisor / Monitor;
+- SVSL --> Low-side (VCORE) Supply Voltage Supervisor, handled by the hardware;
+
+-- SVSMH: --
+
+SUPERVISOR --> A POR is triggered when VCC drops below the threshold;
+MONITOR --> The SVSMH interrupt flag is set (and SVMHOUT driven, if enabled) when VCC drops below
+            the threshold, so the application can save its state before a brown-out;
+
+The threshold is selected with SVSMHTH (level 0 is the lowest, level 7 the highest, see the
+device datasheet for the exact voltages). In full performance mode the SVSMH reacts faster, in
+low power normal performance mode it draws less current in LPM3, LPM4 and LPMx.5. Turning the
+SVSMH off gives the lowest low power mode current, VCCDET still protects the device.
+
+Every write to PSSCTL0 must be preceded by the PSS key (0xA596), the registers are locked again
+by writing any other value.
+
+The VCCDET and SVSMH reset events are reported in RSTCTL_PSSRESET_STAT, see `reset_flags` and
+`reset::ResetControl::clear_reset_flags`.
+*/
+
+use pac::{PSS, RSTCTL};
+
+const PSS_KEY: u32 = 0xA596;
+const PSS_LOCK: u32 = 0x0000;
+
+const SVSMH_OFF: u32 = 0x0001;
+const SVSMH_LOW_POWER: u32 = 0x0002;
+const SVSMH_MONITOR: u32 = 0x0004;
+const SVSMH_THRESHOLD_OFFSET: u32 = 3;
+const SVSMH_THRESHOLD_MASK: u32 = 0x0038;
+const SVMHOUT_ENABLE: u32 = 0x0040;
+const SVMHOUT_ACTIVE_LOW: u32 = 0x0080;
+const SVSMH_FLAG: u32 = 0x0002;
+
+const RESET_SVSL: u32 = 0x01;
+const RESET_SVSMH: u32 = 0x02;
+const RESET_BGREF: u32 = 0x04;
+const RESET_VCCDET: u32 = 0x08;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SvsmhThreshold {
+    Level0 = 0,
+    Level1 = 1,
+    Level2 = 2,
+    Level3 = 3,
+    Level4 = 4,
+    Level5 = 5,
+    Level6 = 6,
+    Level7 = 7,
+}
+
+/// What happens when VCC drops below the SVSMH threshold
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SvsmhAction {
+    /// Supervisor mode, POR
+    Reset,
+    /// Monitor mode, SVSMH interrupt
+    Interrupt,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SvsmhPerformance {
+    /// Full performance in every mode
+    Full,
+    /// Normal performance (lower current) in LPM3, LPM4 and LPMx.5
+    LowPower,
+}
+
+/// PSS events recorded in the reset controller since the flags were last cleared
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PssResetFlags {
+    pub vccdet: bool,
+    pub svsmh: bool,
+    pub svsl: bool,
+    pub bandgap_reference: bool,
+}
+
+pub trait PssExt {
+    fn constrain(self) -> PowerSupply;
+}
+
+impl PssExt for PSS {
+    fn constrain(self) -> PowerSupply {
+        PowerSupply { pss: self }
+    }
+}
+
+pub struct PowerSupply {
+    pss: PSS,
+}
+
+impl PowerSupply {
+    /// Configure and enable the SVSMH
+    pub fn configure_svsmh(
+        &mut self,
+        threshold: SvsmhThreshold,
+        action: SvsmhAction,
+        performance: SvsmhPerformance,
+    ) -> &mut Self {
+        let mut value = (threshold as u32) << SVSMH_THRESHOLD_OFFSET;
+
+        if action == SvsmhAction::Interrupt {
+            value |= SVSMH_MONITOR;
+        }
+
+        if performance == SvsmhPerformance::LowPower {
+            value |= SVSMH_LOW_POWER;
+        }
+
+        self.set_reg_mask(
+            value,
+            SVSMH_OFF | SVSMH_LOW_POWER | SVSMH_MONITOR | SVSMH_THRESHOLD_MASK,
+        );
+        self
+    }
+
+    #[inline]
+    pub fn enable_svsmh(&mut self) -> &mut Self {
+        self.set_reg_mask(0, SVSMH_OFF);
+        self
+    }
+
+    /// Turn the SVSMH off (lowest low power mode current, only VCCDET is left)
+    #[inline]
+    pub fn disable_svsmh(&mut self) -> &mut Self {
+        self.set_reg_mask(SVSMH_OFF, SVSMH_OFF);
+        self
+    }
+
+    #[inline]
+    pub fn is_svsmh_enabled(&self) -> bool {
+        self.pss.pssctl0.read().bits() & SVSMH_OFF == 0
+    }
+
+    pub fn svsmh_threshold(&self) -> SvsmhThreshold {
+        match (self.pss.pssctl0.read().bits() & SVSMH_THRESHOLD_MASK) >> SVSMH_THRESHOLD_OFFSET {
+            0 => SvsmhThreshold::Level0,
+            1 => SvsmhThreshold::Level1,
+            2 => SvsmhThreshold::Level2,
+            3 => SvsmhThreshold::Level3,
+            4 => SvsmhThreshold::Level4,
+            5 => SvsmhThreshold::Level5,
+            6 => SvsmhThreshold::Level6,
+            _ => SvsmhThreshold::Level7,
+        }
+    }
+
+    pub fn svsmh_action(&self) -> SvsmhAction {
+        if self.pss.pssctl0.read().bits() & SVSMH_MONITOR != 0 {
+            SvsmhAction::Interrupt
+        } else {
+            SvsmhAction::Reset
+        }
+    }
+
+    /// Drive the SVMHOUT pin in monitor mode (the pin must be set to its module function)
+    pub fn set_svmhout(&mut self, enable: bool, active_low: bool) -> &mut Self {
+        let mut value = 0;
+
+        if enable {
+            value |= SVMHOUT_ENABLE;
+        }
+
+        if active_low {
+            value |= SVMHOUT_ACTIVE_LOW;
+        }
+
+        self.set_reg_mask(value, SVMHOUT_ENABLE | SVMHOUT_ACTIVE_LOW);
+        self
+    }
+
+    #[inline]
+    pub fn enable_interrupt(&mut self, enable: bool) -> &mut Self {
+        self.pss.pssie.write(|w| unsafe { w.bits(if enable { SVSMH_FLAG } else { 0 }) });
+        self
+    }
+
+    /// VCC dropped below the SVSMH threshold (monitor mode)
+    #[inline]
+    pub fn check_interrupt(&self) -> bool {
+        self.pss.pssifg.read().bits() & SVSMH_FLAG != 0
+    }
+
+    #[inline]
+    pub fn clear_interrupt(&mut self) -> &mut Self {
+        self.pss.pssclrifg.write(|w| unsafe { w.bits(SVSMH_FLAG) });
+        self
+    }
+
+    /// VCCDET and supervisor events reported by the reset controller
+    pub fn reset_flags(&self) -> PssResetFlags {
+        // Read-only access, the flags are owned (and cleared) by `reset::ResetControl`
+        let status = unsafe { (*RSTCTL::ptr()).rstctl_pssreset_stat.read().bits() };
+
+        PssResetFlags {
+            vccdet: status & RESET_VCCDET != 0,
+            svsmh: status & RESET_SVSMH != 0,
+            svsl: status & RESET_SVSL != 0,
+            bandgap_reference: status & RESET_BGREF != 0,
+        }
+    }
+
+    fn set_reg_mask(&self, value: u32, mask: u32) {
+        self.pss.psskey.write(|w| unsafe { w.bits(PSS_KEY) });
+        self.pss.pssctl0.modify(|r, w| unsafe { w.bits((r.bits() & !mask) | value) });
+        self.pss.psskey.write(|w| unsafe { w.bits(PSS_LOCK) });
+    }
+}
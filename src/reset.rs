@@ -0,0 +1,335 @@
+//! HAL library for RSTCTL (Reset Controller) - MSP432P401R
+
+/*
+-- RESET CLASSES (from the most to the least severe): --
+
+POWER ON RESET (POR) --> PSS (VCC detector, SVSMH, SVSL, band-gap reference), PCM (LPMx.5 wake-up)
+                         and CS (DCO external resistor short circuit) events;
+REBOOT RESET --> Reboot requested through SYSCTL, same as POR but without losing the debugger;
+HARD RESET --> Resets the whole system except the debug logic and the backup domain;
+SOFT RESET --> Resets the execution (CPU and watchdog) only, peripherals keep their state;
+
+-- HARD RESET SOURCES: --
+
+SRC0 --> SYSRESETREQ (Cortex-M4 AIRCR);
+SRC1 --> WDT_A time-out (when configured as hard reset in SYSCTL);
+SRC2 --> WDT_A password violation (when configured as hard reset in SYSCTL);
+SRC3 --> FLCTL;
+SRC14 --> CS;
+SRC15 --> PCM;
+
+-- SOFT RESET SOURCES: --
+
+SRC0 --> CPU lockup;
+SRC1 --> WDT_A time-out;
+SRC2 --> WDT_A password violation;
+
+The status registers keep their flags until cleared, so they must be cleared after being read or
+the next boot will report the same cause again.
+*/
+
+use core::arch::asm;
+
+use pac::RSTCTL;
+
+const RESET_KEY: u32 = 0x6900;
+const SOFT_RESET_REQUEST: u32 = 0x01;
+const HARD_RESET_REQUEST: u32 = 0x02;
+const CLEAR_ALL: u32 = 0xFFFF;
+const CLEAR: u32 = 0x01;
+
+const PSS_SVSL: u32 = 0x01;
+const PSS_SVSMH: u32 = 0x02;
+const PSS_BGREF: u32 = 0x04;
+const PSS_VCCDET: u32 = 0x08;
+const PCM_LPM35: u32 = 0x01;
+const PCM_LPM45: u32 = 0x02;
+
+const SYSTEM_RESET_REQUEST: u8 = 0;
+const WATCHDOG_TIMEOUT: u8 = 1;
+const WATCHDOG_PASSWORD: u8 = 2;
+const FLASH_CONTROLLER: u8 = 3;
+const CLOCK_SYSTEM: u8 = 14;
+const POWER_CONTROL_MANAGER: u8 = 15;
+const CPU_LOCKUP: u8 = 0;
+const RESET_SOURCES: u8 = 16;
+
+/// Reset class that handled a watchdog event (configured in SYSCTL)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ResetKind {
+    Hard,
+    Soft,
+}
+
+/// Most severe reset cause recorded since the flags were last cleared
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ResetCause {
+    /// VCC detector, power-on or VCC dropped below the minimum
+    PowerOn,
+    /// High-side supply voltage supervisor (brown-out)
+    SupplySupervisorHigh,
+    /// Low-side (core) supply voltage supervisor
+    SupplySupervisorLow,
+    /// Band-gap reference failure
+    BandgapReference,
+    /// Wake-up from LPM3.5
+    Lpm35Wakeup,
+    /// Wake-up from LPM4.5
+    Lpm45Wakeup,
+    /// DCO external resistor short circuit
+    DcoShortCircuit,
+    /// Reboot requested through SYSCTL
+    Reboot,
+    /// RSTn/NMI pin
+    Pin,
+    /// SYSRESETREQ from the Cortex-M4 (`cortex_m::peripheral::SCB::sys_reset`)
+    SystemResetRequest,
+    WatchdogTimeout(ResetKind),
+    WatchdogPasswordViolation(ResetKind),
+    FlashController,
+    ClockSystem,
+    PowerControlManager,
+    CpuLockup,
+    /// Any other hard reset source (software triggered with `hard_reset_with_source`)
+    HardResetSource(u8),
+    /// Any other soft reset source (software triggered with `soft_reset_with_source`)
+    SoftResetSource(u8),
+    /// No flag set (e.g. `hard_reset()`/`soft_reset()`, or flags already cleared)
+    Unknown,
+}
+
+impl ResetCause {
+    /// Most severe cause from the raw status registers (PSS, PCM, CS, reboot, pin, hard, soft)
+    ///
+    /// Classes are checked from POR down to soft reset, the lowest source wins within the hard
+    /// and soft reset status.
+    pub fn decode(
+        pss: u32,
+        pcm: u32,
+        cs: u32,
+        reboot: u32,
+        pin: u32,
+        hard: u16,
+        soft: u16,
+    ) -> Self {
+        if pss & PSS_VCCDET != 0 {
+            ResetCause::PowerOn
+        } else if pss & PSS_SVSMH != 0 {
+            ResetCause::SupplySupervisorHigh
+        } else if pss & PSS_SVSL != 0 {
+            ResetCause::SupplySupervisorLow
+        } else if pss & PSS_BGREF != 0 {
+            ResetCause::BandgapReference
+        } else if pcm & PCM_LPM35 != 0 {
+            ResetCause::Lpm35Wakeup
+        } else if pcm & PCM_LPM45 != 0 {
+            ResetCause::Lpm45Wakeup
+        } else if cs & CLEAR != 0 {
+            ResetCause::DcoShortCircuit
+        } else if reboot & CLEAR != 0 {
+            ResetCause::Reboot
+        } else if pin & CLEAR != 0 {
+            ResetCause::Pin
+        } else if let Some(source) = lowest_source(hard) {
+            match source {
+                SYSTEM_RESET_REQUEST => ResetCause::SystemResetRequest,
+                WATCHDOG_TIMEOUT => ResetCause::WatchdogTimeout(ResetKind::Hard),
+                WATCHDOG_PASSWORD => ResetCause::WatchdogPasswordViolation(ResetKind::Hard),
+                FLASH_CONTROLLER => ResetCause::FlashController,
+                CLOCK_SYSTEM => ResetCause::ClockSystem,
+                POWER_CONTROL_MANAGER => ResetCause::PowerControlManager,
+                source => ResetCause::HardResetSource(source),
+            }
+        } else if let Some(source) = lowest_source(soft) {
+            match source {
+                CPU_LOCKUP => ResetCause::CpuLockup,
+                WATCHDOG_TIMEOUT => ResetCause::WatchdogTimeout(ResetKind::Soft),
+                WATCHDOG_PASSWORD => ResetCause::WatchdogPasswordViolation(ResetKind::Soft),
+                source => ResetCause::SoftResetSource(source),
+            }
+        } else {
+            ResetCause::Unknown
+        }
+    }
+}
+
+pub trait ResetExt {
+    fn constrain(self) -> ResetControl;
+}
+
+impl ResetExt for RSTCTL {
+    fn constrain(self) -> ResetControl {
+        ResetControl { rstctl: self }
+    }
+}
+
+pub struct ResetControl {
+    rstctl: RSTCTL,
+}
+
+impl ResetControl {
+    /// Decode the most severe reset cause from the status registers
+    pub fn reset_cause(&self) -> ResetCause {
+        ResetCause::decode(
+            self.rstctl.rstctl_pssreset_stat.read().bits(),
+            self.rstctl.rstctl_pcmreset_stat.read().bits(),
+            self.rstctl.rstctl_csreset_stat.read().bits(),
+            self.rstctl.rstctl_rebootreset_stat.read().bits(),
+            self.rstctl.rstctl_pinreset_stat.read().bits(),
+            self.hard_reset_sources(),
+            self.soft_reset_sources(),
+        )
+    }
+
+    /// Raw hard reset status (one bit per source)
+    #[inline]
+    pub fn hard_reset_sources(&self) -> u16 {
+        self.rstctl.rstctl_hardreset_stat.read().bits() as u16
+    }
+
+    /// Raw soft reset status (one bit per source)
+    #[inline]
+    pub fn soft_reset_sources(&self) -> u16 {
+        self.rstctl.rstctl_softreset_stat.read().bits() as u16
+    }
+
+    /// Clear every reset status flag
+    pub fn clear_reset_flags(&mut self) -> &mut Self {
+        self.rstctl.rstctl_hardreset_clr.write(|w| unsafe { w.bits(CLEAR_ALL) });
+        self.rstctl.rstctl_softreset_clr.write(|w| unsafe { w.bits(CLEAR_ALL) });
+        self.rstctl.rstctl_pssreset_clr.write(|w| unsafe { w.bits(CLEAR) });
+        self.rstctl.rstctl_pcmreset_clr.write(|w| unsafe { w.bits(CLEAR) });
+        self.rstctl.rstctl_pinreset_clr.write(|w| unsafe { w.bits(CLEAR) });
+        self.rstctl.rstctl_rebootreset_clr.write(|w| unsafe { w.bits(CLEAR) });
+        self.rstctl.rstctl_csreset_clr.write(|w| unsafe { w.bits(CLEAR) });
+        self
+    }
+
+    /// Trigger a soft reset (CPU and watchdog only)
+    pub fn soft_reset(&self) -> ! {
+        self.rstctl.rstctl_reset_req.write(|w| unsafe { w.bits(RESET_KEY | SOFT_RESET_REQUEST) });
+        wait_reset()
+    }
+
+    /// Trigger a hard reset (whole system except debug and backup domain)
+    pub fn hard_reset(&self) -> ! {
+        self.rstctl.rstctl_reset_req.write(|w| unsafe { w.bits(RESET_KEY | HARD_RESET_REQUEST) });
+        wait_reset()
+    }
+
+    /// Trigger a soft reset recorded as `source` (0 - 15) in the soft reset status
+    ///
+    /// Panics if `source` is out of range.
+    pub fn soft_reset_with_source(&self, source: u8) -> ! {
+        assert!(source < RESET_SOURCES, "soft reset source out of range");
+        self.rstctl.rstctl_softreset_set.write(|w| unsafe { w.bits(1 << source) });
+        wait_reset()
+    }
+
+    /// Trigger a hard reset recorded as `source` (0 - 15) in the hard reset status
+    ///
+    /// Panics if `source` is out of range.
+    pub fn hard_reset_with_source(&self, source: u8) -> ! {
+        assert!(source < RESET_SOURCES, "hard reset source out of range");
+        self.rstctl.rstctl_hardreset_set.write(|w| unsafe { w.bits(1 << source) });
+        wait_reset()
+    }
+}
+
+#[inline]
+fn lowest_source(status: u16) -> Option<u8> {
+    if status == 0 {
+        None
+    } else {
+        Some(status.trailing_zeros() as u8)
+    }
+}
+
+#[inline]
+fn wait_reset() -> ! {
+    loop {
+        unsafe { asm!("NOP") };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_hard_soft(hard: u16, soft: u16) -> ResetCause {
+        ResetCause::decode(0, 0, 0, 0, 0, hard, soft)
+    }
+
+    #[test]
+    fn power_on_first() {
+        let all = ResetCause::decode(0x0F, 0x03, 1, 1, 1, 0xFFFF, 0xFFFF);
+        assert_eq!(all, ResetCause::PowerOn);
+
+        // POR over SVSMH, then the other PSS and PCM sources
+        assert_eq!(
+            ResetCause::decode(PSS_VCCDET | PSS_SVSMH, 0, 0, 0, 0, 0, 0),
+            ResetCause::PowerOn
+        );
+        assert_eq!(
+            ResetCause::decode(PSS_SVSMH | PSS_SVSL, 0, 0, 0, 0, 0, 0),
+            ResetCause::SupplySupervisorHigh
+        );
+        assert_eq!(
+            ResetCause::decode(PSS_SVSL | PSS_BGREF, 0, 0, 0, 0, 0, 0),
+            ResetCause::SupplySupervisorLow
+        );
+        assert_eq!(
+            ResetCause::decode(PSS_BGREF, PCM_LPM35, 0, 0, 0, 0, 0),
+            ResetCause::BandgapReference
+        );
+        assert_eq!(
+            ResetCause::decode(0, PCM_LPM35 | PCM_LPM45, 1, 0, 0, 0, 0),
+            ResetCause::Lpm35Wakeup
+        );
+        assert_eq!(ResetCause::decode(0, PCM_LPM45, 1, 0, 0, 0, 0), ResetCause::Lpm45Wakeup);
+    }
+
+    #[test]
+    fn reboot_and_pin_over_hard() {
+        assert_eq!(ResetCause::decode(0, 0, 1, 1, 1, 0x0001, 0), ResetCause::DcoShortCircuit);
+        assert_eq!(ResetCause::decode(0, 0, 0, 1, 1, 0x0001, 0), ResetCause::Reboot);
+        assert_eq!(ResetCause::decode(0, 0, 0, 0, 1, 0x0001, 0x0001), ResetCause::Pin);
+    }
+
+    #[test]
+    fn hard_over_soft() {
+        assert_eq!(decode_hard_soft(0x0002, 0x0001), ResetCause::WatchdogTimeout(ResetKind::Hard));
+        assert_eq!(decode_hard_soft(0, 0x0002), ResetCause::WatchdogTimeout(ResetKind::Soft));
+        assert_eq!(decode_hard_soft(0, 0x0001), ResetCause::CpuLockup);
+    }
+
+    #[test]
+    fn lowest_source_wins() {
+        assert_eq!(decode_hard_soft(0xC00F, 0), ResetCause::SystemResetRequest);
+        assert_eq!(
+            decode_hard_soft(0xC00C, 0),
+            ResetCause::WatchdogPasswordViolation(ResetKind::Hard)
+        );
+        assert_eq!(decode_hard_soft(0xC008, 0), ResetCause::FlashController);
+        assert_eq!(decode_hard_soft(0xC010, 0), ResetCause::HardResetSource(4));
+        assert_eq!(decode_hard_soft(0xC000, 0), ResetCause::ClockSystem);
+        assert_eq!(decode_hard_soft(0x8000, 0), ResetCause::PowerControlManager);
+
+        assert_eq!(decode_hard_soft(0, 0x0006), ResetCause::WatchdogTimeout(ResetKind::Soft));
+        assert_eq!(
+            decode_hard_soft(0, 0x0004),
+            ResetCause::WatchdogPasswordViolation(ResetKind::Soft)
+        );
+        assert_eq!(decode_hard_soft(0, 0x8008), ResetCause::SoftResetSource(3));
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(ResetCause::decode(0, 0, 0, 0, 0, 0, 0), ResetCause::Unknown);
+        // Reserved PSS and PCM bits are ignored
+        assert_eq!(
+            ResetCause::decode(0xFFF0, 0xFFFC, 0xFFFE, 0xFFFE, 0xFFFE, 0, 0),
+            ResetCause::Unknown
+        );
+    }
+}
@@ -0,0 +1,579 @@
+//! HAL library for Random Seed generation (VLO jitter) - MSP432P401R
+
+/*
+The MSP432P401R has no true random number generator. The entropy source used here is the jitter
+between two independent oscillators:
+
+- ACLK is sourced from VLOCLK (~9.4 kHz, large period jitter and temperature drift);
+- A TimerA counts SMCLK (DCO or crystal) in continuous mode;
+- The capture unit CCR2 latches the timer on every rising edge of ACLK (CCI2B input);
+
+The difference between two captures is the number of SMCLK cycles in one VLO period, whose least
+significant bit is the raw noise. Raw bits are debiased with the von Neumann extractor.
+
+Every raw sample passes through the NIST SP 800-90B continuous health tests (assuming 1 bit of
+min-entropy per sample and a false positive rate of 2^-20):
+
+- Repetition Count --> fails after 21 identical consecutive samples;
+- Adaptive Proportion --> fails when one sample value repeats 410 times in a 512 samples window;
+
+A failure latches: every following sample fails with the same error until `Rng::reset` restarts
+the tests and the startup samples.
+
+The generator is slow (two VLO periods per extracted bit at best), so it is meant to seed a
+software generator such as `SeededRng` rather than being used as the random source itself.
+*/
+
+use core::num::NonZeroU32;
+
+use pac::{TIMER_A0, TIMER_A1, TIMER_A2, TIMER_A3};
+use rand_core::{impls, RngCore, SeedableRng};
+
+use crate::clock::{Clocks, VLOCLK};
+
+const CAPTURE_REGISTER: usize = 2;
+
+const TIMER_SMCLK_CONTINUOUS: u16 = 0x0200 | 0x0020;
+const TIMER_CLEAR: u16 = 0x0004;
+const TIMER_MODE_MASK: u16 = 0xFFCF;
+
+const CAPTURE_RISING_EDGE: u16 = 0x4000;
+const CAPTURE_INPUT_B: u16 = 0x1000;
+const CAPTURE_SYNC: u16 = 0x0800;
+const CAPTURE_MODE: u16 = 0x0100;
+const CAPTURE_OVERFLOW: u16 = 0x0002;
+const CAPTURE_FLAG: u16 = 0x0001;
+
+const MIN_CLOCK_RATIO: u32 = 64;
+const STARTUP_SAMPLES: u8 = 16;
+const MAX_DEBIAS_ATTEMPTS: u16 = 1024;
+const CAPTURE_TIMEOUT: u32 = 100_000;
+
+const REPETITION_COUNT_CUTOFF: u16 = 21;
+const ADAPTIVE_PROPORTION_WINDOW: u16 = 512;
+const ADAPTIVE_PROPORTION_CUTOFF: u16 = 410;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// ACLK is not sourced from VLOCLK
+    AclkNotVlo,
+    /// SMCLK is not fast enough compared to ACLK to measure the VLO jitter
+    ClockRatio,
+    /// Repetition Count health test failure
+    RepetitionCount,
+    /// Adaptive Proportion health test failure
+    AdaptiveProportion,
+    /// The debiasing extractor could not produce a bit
+    LowEntropy,
+    /// No ACLK edge was captured (VLO or ACLK stopped)
+    NoCapture,
+}
+
+impl Error {
+    const fn code(&self) -> u32 {
+        rand_core::Error::CUSTOM_START + *self as u32
+    }
+}
+
+impl From<Error> for rand_core::Error {
+    fn from(error: Error) -> Self {
+        NonZeroU32::new(error.code()).unwrap().into()
+    }
+}
+
+/// Continuous health tests (NIST SP 800-90B, section 4.4) over raw noise samples
+#[derive(Debug, Clone)]
+pub struct HealthTests {
+    last: u8,
+    repetitions: u16,
+    reference: u8,
+    matches: u16,
+    window: u16,
+    failure: Option<Error>,
+}
+
+impl HealthTests {
+    pub const fn new() -> Self {
+        HealthTests {
+            last: 0,
+            repetitions: 0,
+            reference: 0,
+            matches: 0,
+            window: 0,
+            failure: None,
+        }
+    }
+
+    /// Account for a new raw sample, failing if the noise source looks stuck
+    ///
+    /// Once a test failed, every sample fails with the same error until `reset`.
+    pub fn feed(&mut self, sample: u8) -> Result<(), Error> {
+        if let Some(error) = self.failure {
+            return Err(error);
+        }
+
+        self.check(sample).map_err(|error| {
+            self.failure = Some(error);
+            error
+        })
+    }
+
+    /// Latched test failure
+    #[inline]
+    pub fn failure(&self) -> Option<Error> {
+        self.failure
+    }
+
+    /// Clear the failure and restart both tests
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    fn check(&mut self, sample: u8) -> Result<(), Error> {
+        if self.repetitions != 0 && sample == self.last {
+            self.repetitions += 1;
+            if self.repetitions >= REPETITION_COUNT_CUTOFF {
+                return Err(Error::RepetitionCount);
+            }
+        } else {
+            self.last = sample;
+            self.repetitions = 1;
+        }
+
+        if self.window == 0 {
+            self.reference = sample;
+            self.matches = 1;
+        } else if sample == self.reference {
+            self.matches += 1;
+            if self.matches >= ADAPTIVE_PROPORTION_CUTOFF {
+                return Err(Error::AdaptiveProportion);
+            }
+        }
+        self.window = (self.window + 1) % ADAPTIVE_PROPORTION_WINDOW;
+
+        Ok(())
+    }
+}
+
+impl Default for HealthTests {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// TimerA used to capture ACLK edges against SMCLK
+pub trait CaptureTimer {
+    fn start_capture(&self);
+    fn stop_capture(&self);
+    /// Wait for the next ACLK rising edge and return the captured count
+    fn capture(&self) -> Result<u16, Error>;
+}
+
+macro_rules! capture_timer {
+    ($($TIMER:ident),*) => {
+        $(
+            impl CaptureTimer for $TIMER {
+                fn start_capture(&self) {
+                    self.tax_ctl.write(|w| unsafe { w.bits(TIMER_CLEAR) });
+                    self.tax_cctl[CAPTURE_REGISTER].write(|w| unsafe {
+                        w.bits(CAPTURE_RISING_EDGE | CAPTURE_INPUT_B | CAPTURE_SYNC | CAPTURE_MODE)
+                    });
+                    self.tax_ctl.write(|w| unsafe { w.bits(TIMER_SMCLK_CONTINUOUS) });
+                }
+
+                fn stop_capture(&self) {
+                    self.tax_ctl.modify(|r, w| unsafe { w.bits(r.bits() & TIMER_MODE_MASK) });
+                    self.tax_cctl[CAPTURE_REGISTER].write(|w| unsafe { w.bits(0) });
+                }
+
+                fn capture(&self) -> Result<u16, Error> {
+                    let mut attempts: u32 = 0;
+
+                    while self.tax_cctl[CAPTURE_REGISTER].read().bits() & CAPTURE_FLAG == 0 {
+                        attempts += 1;
+                        if attempts >= CAPTURE_TIMEOUT {
+                            return Err(Error::NoCapture);
+                        }
+                    }
+
+                    let count = self.tax_ccr[CAPTURE_REGISTER].read().bits();
+                    self.tax_cctl[CAPTURE_REGISTER].modify(|r, w| unsafe {
+                        w.bits(r.bits() & !(CAPTURE_FLAG | CAPTURE_OVERFLOW))
+                    });
+                    Ok(count)
+                }
+            }
+        )*
+    };
+}
+
+capture_timer!(TIMER_A0, TIMER_A1, TIMER_A2, TIMER_A3);
+
+pub trait RngExt: CaptureTimer + Sized {
+    /// Use this timer as the VLO jitter entropy source. ACLK must be sourced from VLOCLK.
+    ///
+    /// The timer is handed back with the error if the clocks or the startup samples fail.
+    fn into_rng(self, clocks: &Clocks) -> Result<Rng<Self>, (Self, Error)> {
+        Rng::new(self, clocks)
+    }
+}
+
+impl<T: CaptureTimer> RngExt for T {}
+
+/// Entropy source built from the VLO jitter
+pub struct Rng<T: CaptureTimer> {
+    timer: T,
+    health: HealthTests,
+    previous: u16,
+}
+
+impl<T: CaptureTimer> Rng<T> {
+    fn new(timer: T, clocks: &Clocks) -> Result<Self, (T, Error)> {
+        if clocks.aclk.0 != VLOCLK {
+            return Err((timer, Error::AclkNotVlo));
+        }
+
+        if clocks.smclk.0 / clocks.aclk.0 < MIN_CLOCK_RATIO {
+            return Err((timer, Error::ClockRatio));
+        }
+
+        timer.start_capture();
+
+        let mut rng = Rng {
+            timer,
+            health: HealthTests::new(),
+            previous: 0,
+        };
+
+        match rng.reset() {
+            Ok(()) => Ok(rng),
+            Err(error) => Err((rng.free(), error)),
+        }
+    }
+
+    /// Clear a health test failure and run the startup samples again
+    pub fn reset(&mut self) -> Result<(), Error> {
+        self.health.reset();
+        self.previous = self.timer.capture()?;
+        self.startup()
+    }
+
+    fn startup(&mut self) -> Result<(), Error> {
+        for _ in 0..STARTUP_SAMPLES {
+            self.sample()?;
+        }
+        Ok(())
+    }
+
+    /// Raw noise sample: SMCLK cycles in one VLO period (lower 8 bits)
+    ///
+    /// Fails once a health test failed, until `reset`, or if no ACLK edge is captured.
+    pub fn sample(&mut self) -> Result<u8, Error> {
+        let count = self.timer.capture()?;
+        let sample = count.wrapping_sub(self.previous) as u8;
+
+        self.previous = count;
+        self.health.feed(sample)?;
+        Ok(sample)
+    }
+
+    /// One debiased random bit
+    pub fn next_bit(&mut self) -> Result<bool, Error> {
+        for _ in 0..MAX_DEBIAS_ATTEMPTS {
+            let first = self.sample()? & 0x01;
+            let second = self.sample()? & 0x01;
+
+            if first != second {
+                return Ok(first != 0);
+            }
+        }
+
+        Err(Error::LowEntropy)
+    }
+
+    /// Fill `dest` with debiased random bits
+    pub fn fill_seed(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        for byte in dest.iter_mut() {
+            *byte = 0;
+            for _ in 0..8 {
+                *byte = (*byte << 1) | self.next_bit()? as u8;
+            }
+        }
+        Ok(())
+    }
+
+    /// Seed any `rand_core::SeedableRng` generator
+    pub fn seed<R: SeedableRng>(&mut self) -> Result<R, Error> {
+        let mut seed = R::Seed::default();
+        self.fill_seed(seed.as_mut())?;
+        Ok(R::from_seed(seed))
+    }
+
+    /// Seed the built-in software generator
+    pub fn seeded_rng(&mut self) -> Result<SeededRng, Error> {
+        self.seed()
+    }
+
+    /// Stop the capture and release the timer
+    pub fn free(self) -> T {
+        self.timer.stop_capture();
+        self.timer
+    }
+}
+
+impl<T: CaptureTimer> RngCore for Rng<T> {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    /// Panics if a health test or the capture fails, use `try_fill_bytes` to handle it
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.fill_seed(dest).expect("entropy source failure")
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        Ok(self.fill_seed(dest)?)
+    }
+}
+
+/// xoshiro128++ pseudo random generator, meant to be seeded by `Rng`
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    s: [u32; 4],
+}
+
+impl SeedableRng for SeededRng {
+    type Seed = [u8; 16];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        let mut s = [0u32; 4];
+        for (word, chunk) in s.iter_mut().zip(seed.chunks_exact(4)) {
+            *word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+
+        // The all zero state is a fixed point of the generator
+        if s == [0; 4] {
+            return Self::seed_from_u64(0);
+        }
+
+        SeededRng { s }
+    }
+}
+
+impl RngCore for SeededRng {
+    fn next_u32(&mut self) -> u32 {
+        let result = self.s[0].wrapping_add(self.s[3]).rotate_left(7).wrapping_add(self.s[0]);
+        let t = self.s[1] << 9;
+
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(11);
+
+        result
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::cell::Cell;
+
+    use super::*;
+    use crate::time::Hertz;
+
+    /// Feed `count` samples, returning the index of the first failure
+    fn feed(health: &mut HealthTests, count: usize, sample: impl Fn(usize) -> u8) -> Option<usize> {
+        (0..count).find(|&index| health.feed(sample(index)).is_err())
+    }
+
+    #[test]
+    fn repetition_count() {
+        let mut health = HealthTests::new();
+
+        // 20 identical samples pass, the 21st fails
+        assert_eq!(feed(&mut health, 20, |_| 0x5A), None);
+        assert_eq!(health.feed(0x5A), Err(Error::RepetitionCount));
+
+        // A different sample restarts the count
+        let mut health = HealthTests::new();
+        assert_eq!(feed(&mut health, 60, |index| (index / 20) as u8), None);
+    }
+
+    #[test]
+    fn adaptive_proportion() {
+        // 0 is the window reference, 409 times in the first 511 samples (never 21 in a row)
+        let sample = |index: usize| {
+            let index = index % 512;
+            if index % 5 == 4 || index == 511 {
+                index as u8 | 0x01
+            } else {
+                0
+            }
+        };
+
+        let mut health = HealthTests::new();
+        assert_eq!(feed(&mut health, 511, sample), None);
+        // The 410th fails
+        assert_eq!(health.feed(0), Err(Error::AdaptiveProportion));
+
+        // Each window starts a new count
+        let mut health = HealthTests::new();
+        assert_eq!(feed(&mut health, 4 * 512, sample), None);
+    }
+
+    #[test]
+    fn failure_latches() {
+        let mut health = HealthTests::new();
+
+        assert_eq!(feed(&mut health, 21, |_| 0), Some(20));
+        // Differing samples (and a new window) still fail
+        assert_eq!(health.feed(1), Err(Error::RepetitionCount));
+        assert_eq!(feed(&mut health, 1024, |index| index as u8), Some(0));
+        assert_eq!(health.failure(), Some(Error::RepetitionCount));
+
+        health.reset();
+        assert_eq!(health.failure(), None);
+        assert_eq!(feed(&mut health, 1024, |index| index as u8), None);
+    }
+
+    /// Capture timer whose counts advance by `steps` in turn, ACLK stops after `captures`
+    struct MockTimer {
+        steps: &'static [u16],
+        captures: usize,
+        count: Cell<u16>,
+        index: Cell<usize>,
+        running: Cell<bool>,
+    }
+
+    impl MockTimer {
+        fn new(steps: &'static [u16], captures: usize) -> Self {
+            MockTimer {
+                steps,
+                captures,
+                count: Cell::new(0),
+                index: Cell::new(0),
+                running: Cell::new(false),
+            }
+        }
+    }
+
+    impl CaptureTimer for MockTimer {
+        fn start_capture(&self) {
+            self.running.set(true);
+        }
+
+        fn stop_capture(&self) {
+            self.running.set(false);
+        }
+
+        fn capture(&self) -> Result<u16, Error> {
+            let index = self.index.get();
+            if index >= self.captures {
+                return Err(Error::NoCapture);
+            }
+
+            self.index.set(index + 1);
+            self.count.set(self.count.get().wrapping_add(self.steps[index % self.steps.len()]));
+            Ok(self.count.get())
+        }
+    }
+
+    fn clocks(aclk: u32, smclk: u32) -> Clocks {
+        Clocks {
+            aclk: Hertz(aclk),
+            mclk: Hertz(smclk),
+            hsmclk: Hertz(smclk),
+            smclk: Hertz(smclk),
+            bclk: Hertz(aclk),
+        }
+    }
+
+    const JITTER: &[u16] = &[5106, 5107, 5107, 5106, 5105, 5108, 5106, 5105, 5107];
+
+    #[test]
+    fn clock_errors_hand_back_the_timer() {
+        let timer = MockTimer::new(JITTER, usize::MAX);
+        let (timer, error) = timer.into_rng(&clocks(32768, 48_000_000)).err().unwrap();
+        assert_eq!(error, Error::AclkNotVlo);
+        assert!(!timer.running.get());
+        assert_eq!(timer.index.get(), 0);
+
+        let timer = MockTimer::new(JITTER, usize::MAX);
+        let (timer, error) = timer.into_rng(&clocks(VLOCLK, 500_000)).err().unwrap();
+        assert_eq!(error, Error::ClockRatio);
+        assert_eq!(timer.index.get(), 0);
+    }
+
+    #[test]
+    fn startup_errors_hand_back_the_timer() {
+        let timer = MockTimer::new(JITTER, 0);
+        let (timer, error) = timer.into_rng(&clocks(VLOCLK, 48_000_000)).err().unwrap();
+        assert_eq!(error, Error::NoCapture);
+        assert!(!timer.running.get());
+
+        // ACLK stops during the startup samples
+        let timer = MockTimer::new(JITTER, 8);
+        let (timer, error) = timer.into_rng(&clocks(VLOCLK, 48_000_000)).err().unwrap();
+        assert_eq!(error, Error::NoCapture);
+        assert_eq!(timer.index.get(), 8);
+        assert!(!timer.running.get());
+    }
+
+    #[test]
+    fn jitter_source() {
+        let timer = MockTimer::new(JITTER, usize::MAX);
+        let mut rng = timer.into_rng(&clocks(VLOCLK, 48_000_000)).ok().unwrap();
+        assert!(rng.timer.running.get());
+
+        let mut seed = [0u8; 4];
+        assert_eq!(rng.fill_seed(&mut seed), Ok(()));
+
+        let timer = rng.free();
+        assert!(!timer.running.get());
+    }
+
+    #[test]
+    fn xoshiro128plusplus() {
+        let mut seed = [0u8; 16];
+        for (chunk, word) in seed.chunks_exact_mut(4).zip([1u32, 2, 3, 4].iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        let mut rng = SeededRng::from_seed(seed);
+
+        // Reference implementation output
+        let expected = [
+            641, 1573767, 3222811527, 3517856514, 836907274, 4247214768, 3867114732, 1355841295,
+            495546011, 621204420,
+        ];
+        for &value in expected.iter() {
+            assert_eq!(rng.next_u32(), value);
+        }
+    }
+
+    #[test]
+    fn zero_seed() {
+        let mut rng = SeededRng::from_seed([0; 16]);
+
+        assert_ne!(rng.s, [0; 4]);
+        assert_ne!(rng.next_u32(), rng.next_u32());
+    }
+}
@@ -0,0 +1,423 @@
+//! HAL library for RTC_C (Real-Time Clock) - MSP432P401R
+
+/*
+The RTC_C module always runs in calendar mode, counting seconds, minutes, hours, day of week,
+day of month, month and year (0 - 4095) from the 32.768 kHz BCLK source (LFXT or REFO).
+
+- RTCCTL0 and RTCCTL13 are protected by RTCKEY (0xA5 on the upper byte of RTCCTL0);
+- RTCHOLD stops the calendar so the time registers can be written consistently;
+- RTCRDY is set while the calendar registers are safe to be read (not being updated);
+- The calendar registers are kept in binary format (RTCBCD = 0) by this driver.
+
+Leap years follow the Gregorian rules: divisible by 4, except centuries not divisible by 400.
+*/
+
+#[cfg(feature = "chrono")]
+use core::convert::TryFrom;
+
+use pac::RTC_C;
+
+const RTC_KEY: u16 = 0xA500;
+const RTC_KEY_MASK: u16 = 0x00FF;
+const RTC_HOLD: u16 = 0x0040;
+const RTC_BCD: u16 = 0x0080;
+const RTC_READY: u16 = 0x0010;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+const DAYS_TO_UNIX_EPOCH: i64 = 719_468;
+const MAX_YEAR: u16 = 4095;
+const UNIX_EPOCH_YEAR: u16 = 1970;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// A calendar field is out of its valid range
+    InvalidDateTime,
+    /// The timestamp can not be represented by the RTC calendar (1970 - 4095)
+    OutOfRange,
+}
+
+/// Calendar date and time as kept by the RTC_C registers
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct DateTime {
+    year: u16,
+    month: u8,
+    day: u8,
+    hours: u8,
+    minutes: u8,
+    seconds: u8,
+}
+
+/// Returns `true` when `year` has 366 days
+pub const fn is_leap_year(year: u16) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Number of days of `month` (1 - 12) in `year`, 0 for an invalid month
+pub const fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+/// Days since 1970-01-01 of a (valid) civil date, negative before the epoch
+const fn days_from_civil(year: u16, month: u8, day: u8) -> i64 {
+    let y = if month <= 2 { year as i64 - 1 } else { year as i64 };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - DAYS_TO_UNIX_EPOCH
+}
+
+/// Civil date (year, month, day) of a number of days since 1970-01-01
+const fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + DAYS_TO_UNIX_EPOCH;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+impl DateTime {
+    /// Builds a calendar date, validating every field (including February 29th)
+    pub fn new(year: u16, month: u8, day: u8, hours: u8, minutes: u8, seconds: u8) -> Result<Self, Error> {
+        if year > MAX_YEAR || day == 0 || day > days_in_month(year, month) ||
+            hours > 23 || minutes > 59 || seconds > 59 {
+            return Err(Error::InvalidDateTime);
+        }
+
+        Ok(DateTime { year, month, day, hours, minutes, seconds })
+    }
+
+    /// Converts seconds since 1970-01-01 00:00:00 UTC into a calendar date
+    pub fn from_unix_timestamp(timestamp: u64) -> Result<Self, Error> {
+        let days = (timestamp / SECONDS_PER_DAY) as i64;
+        let secs = (timestamp % SECONDS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days);
+
+        if year > MAX_YEAR as i64 {
+            return Err(Error::OutOfRange);
+        }
+
+        Ok(DateTime {
+            year: year as u16,
+            month,
+            day,
+            hours: (secs / 3600) as u8,
+            minutes: ((secs / 60) % 60) as u8,
+            seconds: (secs % 60) as u8,
+        })
+    }
+
+    /// Seconds since 1970-01-01 00:00:00 UTC, `OutOfRange` for dates before the epoch
+    pub fn to_unix_timestamp(&self) -> Result<u64, Error> {
+        if self.year < UNIX_EPOCH_YEAR {
+            return Err(Error::OutOfRange);
+        }
+
+        let days = days_from_civil(self.year, self.month, self.day) as u64;
+        Ok(days * SECONDS_PER_DAY + self.hours as u64 * 3600 + self.minutes as u64 * 60 + self.seconds as u64)
+    }
+
+    /// Day of the week, 0 = Sunday ... 6 = Saturday
+    pub fn day_of_week(&self) -> u8 {
+        // 1970-01-01 was a Thursday
+        (days_from_civil(self.year, self.month, self.day) + 4).rem_euclid(7) as u8
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn hours(&self) -> u8 {
+        self.hours
+    }
+
+    pub fn minutes(&self) -> u8 {
+        self.minutes
+    }
+
+    pub fn seconds(&self) -> u8 {
+        self.seconds
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<DateTime> for chrono::NaiveDateTime {
+    type Error = Error;
+
+    fn try_from(dt: DateTime) -> Result<Self, Self::Error> {
+        chrono::NaiveDate::from_ymd_opt(dt.year as i32, dt.month as u32, dt.day as u32)
+            .and_then(|date| date.and_hms_opt(dt.hours as u32, dt.minutes as u32, dt.seconds as u32))
+            .ok_or(Error::InvalidDateTime)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::NaiveDateTime> for DateTime {
+    type Error = Error;
+
+    fn try_from(dt: chrono::NaiveDateTime) -> Result<Self, Self::Error> {
+        use chrono::{Datelike, Timelike};
+
+        if dt.year() < 0 || dt.year() > MAX_YEAR as i32 {
+            return Err(Error::OutOfRange);
+        }
+
+        // Leap seconds are folded into the last second of the minute
+        DateTime::new(dt.year() as u16, dt.month() as u8, dt.day() as u8,
+                      dt.hour() as u8, dt.minute() as u8, dt.second().min(59) as u8)
+    }
+}
+
+pub trait RtcExt {
+    fn constrain(self) -> Rtc;
+}
+
+impl RtcExt for RTC_C {
+    fn constrain(self) -> Rtc {
+        Rtc::new(self)
+    }
+}
+
+pub struct Rtc {
+    rtc: RTC_C,
+}
+
+impl Rtc {
+    fn new(rtc: RTC_C) -> Self {
+        let rtc = Rtc { rtc };
+
+        rtc.key_lock(false);
+        rtc.rtc.rtcctl13.modify(|r, w| unsafe {
+            w.bits(r.bits() & !RTC_BCD)
+        });
+        rtc.key_lock(true);
+        rtc
+    }
+
+    /// Starts the calendar counting
+    pub fn start(&mut self) -> &mut Self {
+        self.set_hold(false);
+        self
+    }
+
+    /// Stops the calendar counting
+    pub fn stop(&mut self) -> &mut Self {
+        self.set_hold(true);
+        self
+    }
+
+    #[inline]
+    pub fn is_running(&self) -> bool {
+        self.rtc.rtcctl13.read().bits() & RTC_HOLD == 0
+    }
+
+    /// Writes a new calendar date, keeping the previous running state
+    pub fn set_datetime(&mut self, dt: &DateTime) -> &mut Self {
+        let running = self.is_running();
+
+        self.set_hold(true);
+
+        self.rtc.rtctim0.write(|w| unsafe {
+            w.bits(((dt.minutes as u16) << 8) | dt.seconds as u16)
+        });
+        self.rtc.rtctim1.write(|w| unsafe {
+            w.bits(((dt.day_of_week() as u16) << 8) | dt.hours as u16)
+        });
+        self.rtc.rtcdate.write(|w| unsafe {
+            w.bits(((dt.month as u16) << 8) | dt.day as u16)
+        });
+        self.rtc.rtcyear.write(|w| unsafe {
+            w.bits(dt.year)
+        });
+
+        if running {
+            self.set_hold(false);
+        }
+        self
+    }
+
+    /// Reads the current calendar date, `InvalidDateTime` if the calendar was never set (after a
+    /// reset the date registers may hold 0)
+    pub fn datetime(&self) -> Result<DateTime, Error> {
+        loop {
+            self.wait_ready();
+
+            let tim0 = self.rtc.rtctim0.read().bits();
+            let tim1 = self.rtc.rtctim1.read().bits();
+            let date = self.rtc.rtcdate.read().bits();
+            let year = self.rtc.rtcyear.read().bits();
+
+            // Registers are only consistent if no update happened while they were read
+            if tim0 == self.rtc.rtctim0.read().bits() {
+                return DateTime::new(
+                    year & 0x0FFF,
+                    (date >> 8) as u8 & 0x0F,
+                    date as u8 & 0x1F,
+                    tim1 as u8 & 0x1F,
+                    (tim0 >> 8) as u8 & 0x3F,
+                    tim0 as u8 & 0x3F,
+                );
+            }
+        }
+    }
+
+    /// Sets the calendar from seconds since 1970-01-01 00:00:00 UTC
+    pub fn set_unix_timestamp(&mut self, timestamp: u64) -> Result<&mut Self, Error> {
+        let dt = DateTime::from_unix_timestamp(timestamp)?;
+        Ok(self.set_datetime(&dt))
+    }
+
+    /// Current calendar as seconds since 1970-01-01 00:00:00 UTC
+    pub fn unix_timestamp(&self) -> Result<u64, Error> {
+        self.datetime()?.to_unix_timestamp()
+    }
+
+    fn wait_ready(&self) {
+        if !self.is_running() {
+            return;
+        }
+
+        while self.rtc.rtcctl13.read().bits() & RTC_READY == 0 {}
+    }
+
+    fn set_hold(&self, hold: bool) {
+        self.key_lock(false);
+        self.rtc.rtcctl13.modify(|r, w| unsafe {
+            if hold {
+                w.bits(r.bits() | RTC_HOLD)
+            } else {
+                w.bits(r.bits() & !RTC_HOLD)
+            }
+        });
+        self.key_lock(true);
+    }
+
+    fn key_lock(&self, lock: bool) {
+        self.rtc.rtcctl0.modify(|r, w| unsafe {
+            if lock {
+                w.bits(r.bits() & RTC_KEY_MASK)
+            } else {
+                w.bits((r.bits() & RTC_KEY_MASK) | RTC_KEY)
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leap_years() {
+        assert!(is_leap_year(2000));
+        assert!(is_leap_year(2024));
+        assert!(is_leap_year(1600));
+        assert!(!is_leap_year(1900));
+        assert!(!is_leap_year(2023));
+        assert!(!is_leap_year(2100));
+
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2100, 2), 28);
+        assert_eq!(days_in_month(2023, 13), 0);
+        assert!(DateTime::new(2024, 2, 29, 0, 0, 0).is_ok());
+        assert_eq!(DateTime::new(2023, 2, 29, 0, 0, 0), Err(Error::InvalidDateTime));
+    }
+
+    #[test]
+    fn invalid_fields() {
+        assert_eq!(DateTime::new(2024, 0, 1, 0, 0, 0), Err(Error::InvalidDateTime));
+        assert_eq!(DateTime::new(2024, 1, 0, 0, 0, 0), Err(Error::InvalidDateTime));
+        assert_eq!(DateTime::new(2024, 4, 31, 0, 0, 0), Err(Error::InvalidDateTime));
+        assert_eq!(DateTime::new(2024, 1, 1, 24, 0, 0), Err(Error::InvalidDateTime));
+        assert_eq!(DateTime::new(2024, 1, 1, 0, 60, 0), Err(Error::InvalidDateTime));
+        assert_eq!(DateTime::new(2024, 1, 1, 0, 0, 60), Err(Error::InvalidDateTime));
+        assert_eq!(DateTime::new(4096, 1, 1, 0, 0, 0), Err(Error::InvalidDateTime));
+    }
+
+    #[test]
+    fn day_of_week() {
+        // Thursday, Saturday, Thursday, Monday
+        assert_eq!(DateTime::new(1970, 1, 1, 0, 0, 0).unwrap().day_of_week(), 4);
+        assert_eq!(DateTime::new(2000, 1, 1, 0, 0, 0).unwrap().day_of_week(), 6);
+        assert_eq!(DateTime::new(2024, 2, 29, 0, 0, 0).unwrap().day_of_week(), 4);
+        assert_eq!(DateTime::new(1900, 1, 1, 0, 0, 0).unwrap().day_of_week(), 1);
+    }
+
+    #[test]
+    fn known_timestamps() {
+        let dt = DateTime::new(2000, 3, 1, 12, 34, 56).unwrap();
+        assert_eq!(dt.to_unix_timestamp(), Ok(951_914_096));
+        assert_eq!(DateTime::from_unix_timestamp(951_914_096), Ok(dt));
+
+        let dt = DateTime::new(2038, 1, 19, 3, 14, 8).unwrap();
+        assert_eq!(dt.to_unix_timestamp(), Ok(1 << 31));
+
+        let dt = DateTime::new(1969, 12, 31, 23, 59, 59).unwrap();
+        assert_eq!(dt.to_unix_timestamp(), Err(Error::OutOfRange));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_conversion() {
+        let dt = DateTime::new(2024, 2, 29, 1, 2, 3).unwrap();
+        let naive = chrono::NaiveDateTime::try_from(dt).unwrap();
+
+        assert_eq!(naive.and_utc().timestamp() as u64, dt.to_unix_timestamp().unwrap());
+        assert_eq!(DateTime::try_from(naive), Ok(dt));
+    }
+
+    #[test]
+    fn every_day_round_trip() {
+        let (mut year, mut month, mut day) = (1970, 1, 1);
+        let mut weekday = 4;
+        let mut days = 0;
+
+        while year <= MAX_YEAR {
+            let timestamp = days * SECONDS_PER_DAY + 86_399;
+            let dt = DateTime::new(year, month, day, 23, 59, 59).unwrap();
+
+            assert_eq!(dt.to_unix_timestamp(), Ok(timestamp));
+            assert_eq!(DateTime::from_unix_timestamp(timestamp), Ok(dt));
+            assert_eq!(dt.day_of_week(), weekday);
+
+            day += 1;
+            if day > days_in_month(year, month) {
+                day = 1;
+                month += 1;
+                if month > 12 {
+                    month = 1;
+                    year += 1;
+                }
+            }
+            weekday = (weekday + 1) % 7;
+            days += 1;
+        }
+
+        assert_eq!(
+            DateTime::from_unix_timestamp(days * SECONDS_PER_DAY),
+            Err(Error::OutOfRange)
+        );
+    }
+}
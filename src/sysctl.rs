@@ -0,0 +1,325 @@
+//! HAL library for SYSCTL (System Controller) - MSP432P401R
+
+/*
+-- SRAM: --
+
+The 64 KB SRAM is split in 8 banks of 8 KB (bank n starts at 0x2000_0000 + n * 0x2000).
+
+- SYS_SRAM_BANKEN --> Enabling bank n enables all the banks below it, disabling bank n disables
+                      all the banks above it. Bank 0 is always enabled;
+- SYS_SRAM_BANKRET --> Each enabled bank can individually keep its content in LPM3 and LPM4,
+                       a non-retained bank loses its data. Bank 0 is always retained;
+
+Disabled and non-retained banks reduce the active and low power mode leakage current. The banks
+holding the stack, `.data` and `.bss` (from the `_stack_start` and `__ebss` linker symbols, see
+`memory.x`) can't be disabled or lose their retention.
+
+-- NMI: --
+
+The CS, PSS and PCM interrupts, as well as the RSTn/NMI pin, can be routed to the NMI instead of
+their regular interrupt (the pin is a reset source otherwise).
+
+-- DEVICE INFO: --
+
+The Device Descriptor (TLV) table starts at 0x0020_1000, in the information flash memory.
+
+-- SECURITY: --
+
+The debug security, the JTAG/SWD lock and the IP protected secure zones are set up by the boot code
+from the flash mailbox (see `flash::mailbox`), SYS_SYSTEM_STAT reports which ones are active.
+*/
+
+use core::arch::asm;
+
+use pac::SYSCTL;
+
+use crate::reset::ResetKind;
+use crate::tlv::Tlv;
+
+pub const SRAM_BASE: u32 = 0x2000_0000;
+pub const SRAM_BANK_SIZE: u32 = 0x2000;
+pub const SRAM_BANKS: u8 = 8;
+pub const TLV_BASE: u32 = 0x0020_1000;
+
+const REBOOT_KEY: u32 = 0x6900;
+const REBOOT: u32 = 0x01;
+const SRAM_READY: u32 = 0x0001_0000;
+const SRAM_BANK_MASK: u32 = 0x0000_00FF;
+const NMI_FLAG_OFFSET: u32 = 16;
+const WDT_TIMEOUT_HARD: u32 = 0x01;
+const WDT_VIOLATION_HARD: u32 = 0x02;
+const GLITCH_FILTER: u32 = 0x01;
+const DEBUG_SECURITY: u32 = 0x08;
+const JTAG_SWD_LOCK: u32 = 0x10;
+const IP_PROTECTION: u32 = 0x20;
+const SECURE_DATA_KEY: u32 = 0x695A;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum NmiSource {
+    ClockSystem = 0x01,
+    PowerSupplySystem = 0x02,
+    PowerControlManager = 0x04,
+    /// RSTn/NMI pin configured as NMI instead of reset
+    Pin = 0x08,
+}
+
+/// Peripherals that can be frozen while the CPU is halted by the debugger
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Peripheral {
+    TimerA0 = 0x0001,
+    TimerA1 = 0x0002,
+    TimerA2 = 0x0004,
+    TimerA3 = 0x0008,
+    Timer32 = 0x0010,
+    EusciA0 = 0x0020,
+    EusciA1 = 0x0040,
+    EusciA2 = 0x0080,
+    EusciA3 = 0x0100,
+    EusciB0 = 0x0200,
+    EusciB1 = 0x0400,
+    EusciB2 = 0x0800,
+    EusciB3 = 0x1000,
+    Adc14 = 0x2000,
+    Watchdog = 0x4000,
+    Dma = 0x8000,
+}
+
+/// Security features applied by the boot code
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SecurityStatus {
+    pub debug_security: bool,
+    pub jtag_swd_locked: bool,
+    pub ip_protection: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// Bank number above the number of banks of the device, or bank holding the stack, `.data` or
+    /// `.bss`
+    InvalidBank,
+}
+
+pub trait SysctlExt {
+    fn constrain(self) -> SystemControl;
+}
+
+impl SysctlExt for SYSCTL {
+    fn constrain(self) -> SystemControl {
+        SystemControl { sysctl: self }
+    }
+}
+
+pub struct SystemControl {
+    sysctl: SYSCTL,
+}
+
+impl SystemControl {
+    /// Enable the first `banks` SRAM banks (1 - 8), powering down the ones above
+    ///
+    /// Fails if a bank to power down holds the stack, `.data` or `.bss`.
+    pub fn set_enabled_sram_banks(&mut self, banks: u8) -> Result<&mut Self, Error> {
+        if banks <= highest_used_sram_bank() || banks > SRAM_BANKS {
+            return Err(Error::InvalidBank);
+        }
+
+        self.wait_sram();
+        self.sysctl.sys_sram_banken.write(|w| unsafe { w.bits((1 << banks) - 1) });
+        self.wait_sram();
+        Ok(self)
+    }
+
+    /// Number of enabled SRAM banks
+    pub fn enabled_sram_banks(&self) -> u8 {
+        (self.sysctl.sys_sram_banken.read().bits() & SRAM_BANK_MASK).count_ones() as u8
+    }
+
+    /// Keep (or not) the content of SRAM `bank` (1 - 7) in LPM3 and LPM4
+    ///
+    /// The retention of a bank up to the one holding the stack, `.data` or `.bss` can't be
+    /// removed.
+    pub fn set_sram_bank_retention(&mut self, bank: u8, retain: bool) -> Result<&mut Self, Error> {
+        if bank == 0 || bank >= SRAM_BANKS {
+            return Err(Error::InvalidBank);
+        }
+
+        if !retain && bank <= highest_used_sram_bank() {
+            return Err(Error::InvalidBank);
+        }
+
+        self.wait_sram();
+        self.sysctl.sys_sram_bankret.modify(|r, w| unsafe {
+            if retain {
+                w.bits(r.bits() | (1 << bank))
+            } else {
+                w.bits(r.bits() & !(1 << bank))
+            }
+        });
+        self.wait_sram();
+        Ok(self)
+    }
+
+    #[inline]
+    pub fn sram_bank_retained(&self, bank: u8) -> bool {
+        bank < SRAM_BANKS && self.sysctl.sys_sram_bankret.read().bits() & (1 << bank) != 0
+    }
+
+    /// Route `source` to the NMI (or back to its regular interrupt / reset)
+    pub fn set_nmi_source(&mut self, source: NmiSource, enable: bool) -> &mut Self {
+        self.sysctl.sys_nmi_ctlstat.modify(|r, w| unsafe {
+            if enable {
+                w.bits(r.bits() | source as u32)
+            } else {
+                w.bits(r.bits() & !(source as u32))
+            }
+        });
+        self
+    }
+
+    /// Whether `source` triggered the current NMI
+    #[inline]
+    pub fn nmi_triggered_by(&self, source: NmiSource) -> bool {
+        self.sysctl.sys_nmi_ctlstat.read().bits() & ((source as u32) << NMI_FLAG_OFFSET) != 0
+    }
+
+    /// Clear the RSTn/NMI pin NMI flag (the other flags are cleared in their own module)
+    #[inline]
+    pub fn clear_nmi_pin_flag(&mut self) -> &mut Self {
+        self.sysctl.sys_nmi_ctlstat.modify(|r, w| unsafe {
+            w.bits(r.bits() & !((NmiSource::Pin as u32) << NMI_FLAG_OFFSET))
+        });
+        self
+    }
+
+    /// Freeze (or not) `peripheral` while the CPU is halted by the debugger
+    pub fn set_halt_on_debug(&mut self, peripheral: Peripheral, halt: bool) -> &mut Self {
+        self.sysctl.sys_perihalt_ctl.modify(|r, w| unsafe {
+            if halt {
+                w.bits(r.bits() | peripheral as u32)
+            } else {
+                w.bits(r.bits() & !(peripheral as u32))
+            }
+        });
+        self
+    }
+
+    /// Reset class triggered by the watchdog time-out and password violation events
+    pub fn set_watchdog_reset(&mut self, timeout: ResetKind, violation: ResetKind) -> &mut Self {
+        let mut value = 0;
+
+        if timeout == ResetKind::Hard {
+            value |= WDT_TIMEOUT_HARD;
+        }
+
+        if violation == ResetKind::Hard {
+            value |= WDT_VIOLATION_HARD;
+        }
+
+        self.sysctl.sys_wdtreset_ctl.write(|w| unsafe { w.bits(value) });
+        self
+    }
+
+    /// Enable (or not) the digital I/O glitch filter
+    pub fn set_glitch_filter(&mut self, enable: bool) -> &mut Self {
+        self.sysctl.sys_dio_gltflt_ctl.write(|w| unsafe {
+            w.bits(if enable { GLITCH_FILTER } else { 0 })
+        });
+        self
+    }
+
+    /// SRAM size in bytes
+    #[inline]
+    pub fn sram_size(&self) -> u32 {
+        self.sysctl.sys_sram_size.read().bits()
+    }
+
+    /// Main flash size in bytes
+    #[inline]
+    pub fn flash_size(&self) -> u32 {
+        self.sysctl.sys_flash_size.read().bits()
+    }
+
+    pub fn security_status(&self) -> SecurityStatus {
+        let status = self.sysctl.sys_system_stat.read().bits();
+
+        SecurityStatus {
+            debug_security: status & DEBUG_SECURITY != 0,
+            jtag_swd_locked: status & JTAG_SWD_LOCK != 0,
+            ip_protection: status & IP_PROTECTION != 0,
+        }
+    }
+
+    /// Unlock the data accesses to the IP protected secure zones (only effective from the code of
+    /// a secure zone)
+    #[inline]
+    pub fn unlock_secure_data(&mut self) -> &mut Self {
+        self.sysctl.sys_secdata_unlock.write(|w| unsafe { w.bits(SECURE_DATA_KEY) });
+        self
+    }
+
+    /// Device Descriptor (TLV) table, see `tlv`
+    #[inline]
+    pub fn device_descriptor(&self) -> Tlv<'static> {
+        Tlv::device()
+    }
+
+    /// Reboot the device (same as a power-on reset, but keeping the debugger connected)
+    pub fn reboot(&self) -> ! {
+        self.sysctl.sys_reboot_ctl.write(|w| unsafe { w.bits(REBOOT_KEY | REBOOT) });
+        loop {
+            unsafe { asm!("NOP") };
+        }
+    }
+
+    fn wait_sram(&self) {
+        while self.sysctl.sys_sram_banken.read().bits() & SRAM_READY == 0 {
+            unsafe { asm!("NOP") };
+        }
+    }
+}
+
+/// Highest SRAM bank holding the stack, `.data` or `.bss`
+#[cfg(target_arch = "arm")]
+fn highest_used_sram_bank() -> u8 {
+    extern "C" {
+        static _stack_start: u32;
+        static __ebss: u32;
+    }
+
+    // Only the addresses of the linker symbols are used
+    let (stack_start, end_of_bss) = unsafe {
+        (&_stack_start as *const u32 as u32, &__ebss as *const u32 as u32)
+    };
+    used_sram_bank(stack_start, end_of_bss)
+}
+
+// Other architectures have no linker symbols (host tests), assume the default `memory.x`
+#[cfg(not(target_arch = "arm"))]
+fn highest_used_sram_bank() -> u8 {
+    used_sram_bank(SRAM_BASE + SRAM_BANKS as u32 * SRAM_BANK_SIZE, SRAM_BASE)
+}
+
+/// Bank of the last byte below `stack_start` (the stack grows down from it) and `end_of_bss`
+fn used_sram_bank(stack_start: u32, end_of_bss: u32) -> u8 {
+    let last = stack_start.max(end_of_bss).max(SRAM_BASE + 1) - 1;
+    ((last - SRAM_BASE) / SRAM_BANK_SIZE).min(SRAM_BANKS as u32 - 1) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn used_banks() {
+        // Default memory.x: the stack starts at the top of the SRAM
+        assert_eq!(used_sram_bank(0x2001_0000, 0x2000_0400), 7);
+
+        // Stack (and `.bss`) ending at a bank boundary
+        assert_eq!(used_sram_bank(0x2000_4000, 0x2000_0400), 1);
+        assert_eq!(used_sram_bank(0x2000_4004, 0x2000_0400), 2);
+        assert_eq!(used_sram_bank(0x2000_2000, 0x2000_5001), 2);
+
+        // Empty `.data`/`.bss` and a stack in bank 0
+        assert_eq!(used_sram_bank(0x2000_1000, SRAM_BASE), 0);
+        assert_eq!(used_sram_bank(SRAM_BASE, SRAM_BASE), 0);
+    }
+}
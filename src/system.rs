@@ -0,0 +1,315 @@
+//! HAL library for the combined PCM / Flash / Clock System configuration - MSP432P401R
+
+/*
+The core voltage, the flash wait states and the clocks depend on each other:
+
+- VCORE0 --> MCLK and HSMCLK up to 24 MHz, SMCLK up to 12 MHz;
+- VCORE1 --> MCLK and HSMCLK up to 48 MHz, SMCLK up to 24 MHz;
+- AM_LF_VCOREx --> Every clock up to 128 kHz, sourced from LFXT, VLO or REFO;
+- Flash wait states --> VCORE0: 0 up to 12 MHz, 1 up to 24 MHz;
+                        VCORE1: 0 up to 16 MHz, 1 up to 32 MHz, 2 up to 48 MHz;
+
+`SystemConfig` picks the lowest power active mode and the minimum wait states for the requested
+clocks and applies them in a safe order:
+
+1. PCM --> Active mode able to run both the current and the requested clocks;
+2. FLCTL --> Wait states for the fastest MCLK of both configurations;
+3. CS --> Requested clocks;
+4. FLCTL --> Wait states for the requested MCLK and the final core voltage;
+5. PCM --> Final (lowest power) active mode;
+
+When speeding up, steps 4 and 5 keep the values of steps 1 and 2. When slowing down, steps 1 and
+2 keep the current values. The current clocks default to the reset configuration (3 MHz DCO).
+
+The LF active mode depends on the clock sources, not only on the frequencies: it is only picked
+(as final mode) when enabled with `low_frequency`.
+
+-- FAILURES: --
+
+`freeze` hands the peripherals back with the error (see `Partial`), in the state the failure left
+them: nothing is applied if no active mode can run the requested clocks, the wait states and the
+clocks are left as-is if the transition active mode fails, and the requested clocks and wait states
+are kept if the final active mode fails (the core voltage can't be lower than the final one).
+*/
+
+use core::fmt;
+
+use crate::clock::{ClockConfig, Clocks, MclkDefined, SmclkDefined, LFXTCLK};
+use crate::flash::{Defined, FlashControl, FlashWaitStates, NotDefined};
+use crate::pcm::{self, PcmConfig, PcmDefined, PcmNotDefined, Regulator, VCoreSel};
+use crate::time::Hertz;
+
+const RESET_DCOCLK: u32 = 3_000_000;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// No active mode can run the requested clocks
+    ClockFrequency,
+    /// Core voltage transition failure
+    Pcm(pcm::Error),
+}
+
+impl From<pcm::Error> for Error {
+    fn from(error: pcm::Error) -> Self {
+        Error::Pcm(error)
+    }
+}
+
+/// Configured PCM, flash controller and clocks
+pub struct System {
+    pub pcm: PcmConfig<PcmDefined>,
+    pub flash: FlashControl<Defined>,
+    pub clocks: Clocks,
+}
+
+/// Peripherals handed back by a failed `SystemConfig::freeze`
+pub enum Partial {
+    /// Nothing was applied
+    Config(SystemConfig),
+    /// The transition active mode failed, the wait states and the clocks were not changed
+    Transition {
+        pcm: PcmConfig<PcmDefined>,
+        flash: FlashControl<NotDefined>,
+        clock: ClockConfig<MclkDefined, SmclkDefined>,
+    },
+    /// The final active mode failed, the requested clocks and wait states are applied
+    System(System),
+}
+
+impl fmt::Debug for Partial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Partial::Config(_) => "Config",
+            Partial::Transition { .. } => "Transition",
+            Partial::System(_) => "System",
+        })
+    }
+}
+
+/// Active modes and wait states of the transition (steps 1 and 2) and of the final configuration
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Plan {
+    transition_mode: VCoreSel,
+    transition_wait: FlashWaitStates,
+    final_mode: VCoreSel,
+    final_wait: FlashWaitStates,
+}
+
+impl Plan {
+    fn new(
+        current: &Clocks,
+        target: &Clocks,
+        regulator: Regulator,
+        low_frequency: bool,
+    ) -> Option<Plan> {
+        let transition = Clocks {
+            aclk: Hertz(target.aclk.0.max(current.aclk.0)),
+            mclk: Hertz(target.mclk.0.max(current.mclk.0)),
+            hsmclk: Hertz(target.hsmclk.0.max(current.hsmclk.0)),
+            smclk: Hertz(target.smclk.0.max(current.smclk.0)),
+            bclk: Hertz(target.bclk.0.max(current.bclk.0)),
+        };
+
+        let final_mode = low_frequency
+            .then(|| VCoreSel::select_low_frequency(target))
+            .flatten()
+            .or_else(|| VCoreSel::select(target, regulator))?;
+        let transition_mode = VCoreSel::select(&transition, regulator)?;
+
+        Some(Plan {
+            transition_mode,
+            transition_wait: FlashWaitStates::minimum(transition.mclk.0, transition_mode)?,
+            final_mode,
+            final_wait: FlashWaitStates::minimum(target.mclk.0, final_mode)?,
+        })
+    }
+}
+
+/// Builder object that configures the core voltage, the flash wait states and the clocks together
+pub struct SystemConfig {
+    pcm: PcmConfig<PcmNotDefined>,
+    flash: FlashControl<NotDefined>,
+    clock: ClockConfig<MclkDefined, SmclkDefined>,
+    regulator: Regulator,
+    low_frequency: bool,
+    current: Clocks,
+}
+
+impl SystemConfig {
+    pub fn new(
+        pcm: PcmConfig<PcmNotDefined>,
+        flash: FlashControl<NotDefined>,
+        clock: ClockConfig<MclkDefined, SmclkDefined>,
+    ) -> Self {
+        SystemConfig {
+            pcm,
+            flash,
+            clock,
+            regulator: Regulator::Ldo,
+            low_frequency: false,
+            current: Clocks {
+                aclk: Hertz(LFXTCLK),
+                mclk: Hertz(RESET_DCOCLK),
+                hsmclk: Hertz(RESET_DCOCLK),
+                smclk: Hertz(RESET_DCOCLK),
+                bclk: Hertz(LFXTCLK),
+            },
+        }
+    }
+
+    /// Regulator used above 128 kHz (default: LDO)
+    pub fn regulator(mut self, regulator: Regulator) -> Self {
+        self.regulator = regulator;
+        self
+    }
+
+    /// Every requested clock is sourced from LFXT, VLO or REFO, allow the LF active mode
+    pub fn low_frequency(mut self) -> Self {
+        self.low_frequency = true;
+        self
+    }
+
+    /// Clocks running before `freeze`, if they are not the reset ones
+    pub fn current_clocks(mut self, clocks: Clocks) -> Self {
+        self.current = clocks;
+        self
+    }
+
+    /// Apply the configuration to hardware
+    pub fn freeze(self) -> Result<System, (Partial, Error)> {
+        let plan = match Plan::new(
+            &self.current,
+            &self.clock.clocks(),
+            self.regulator,
+            self.low_frequency,
+        ) {
+            Some(plan) => plan,
+            None => return Err((Partial::Config(self), Error::ClockFrequency)),
+        };
+
+        let pcm = match self.pcm.set_vcore(plan.transition_mode).freeze() {
+            Ok(pcm) => pcm,
+            Err((pcm, error)) => {
+                let partial = Partial::Transition {
+                    pcm,
+                    flash: self.flash,
+                    clock: self.clock,
+                };
+                return Err((partial, error.into()));
+            }
+        };
+        let flash = self.flash.set_waitstates(plan.transition_wait).freeze();
+
+        let clocks = self.clock.freeze();
+
+        let flash = flash.set_waitstates(plan.final_wait).freeze();
+
+        match pcm.set_vcore(plan.final_mode).freeze() {
+            Ok(pcm) => Ok(System { pcm, flash, clocks }),
+            Err((pcm, error)) => {
+                let partial = Partial::System(System { pcm, flash, clocks });
+                Err((partial, error.into()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MHZ: u32 = 1_000_000;
+
+    fn clocks(mclk: u32, smclk: u32) -> Clocks {
+        Clocks {
+            aclk: Hertz(LFXTCLK),
+            mclk: Hertz(mclk),
+            hsmclk: Hertz(mclk),
+            smclk: Hertz(smclk),
+            bclk: Hertz(LFXTCLK),
+        }
+    }
+
+    fn reset() -> Clocks {
+        clocks(RESET_DCOCLK, RESET_DCOCLK)
+    }
+
+    #[test]
+    fn active_mode_selection() {
+        let select = |mclk, smclk, regulator| VCoreSel::select(&clocks(mclk, smclk), regulator);
+
+        assert_eq!(select(24 * MHZ, 12 * MHZ, Regulator::Ldo), Some(VCoreSel::LdoVcore0));
+        assert_eq!(select(24 * MHZ, 24 * MHZ, Regulator::Ldo), Some(VCoreSel::LdoVcore1));
+        assert_eq!(select(48 * MHZ, 12 * MHZ, Regulator::Dcdc), Some(VCoreSel::DcdcVcore1));
+        assert_eq!(select(48 * MHZ, 48 * MHZ, Regulator::Ldo), None);
+        assert_eq!(select(64 * MHZ, 12 * MHZ, Regulator::Dcdc), None);
+
+        let low_frequency = |mclk| VCoreSel::select_low_frequency(&clocks(mclk, mclk));
+
+        assert_eq!(low_frequency(128_000), Some(VCoreSel::LfVcore0));
+        assert_eq!(low_frequency(128_001), None);
+    }
+
+    #[test]
+    fn speed_up() {
+        let plan = Plan::new(&reset(), &clocks(48 * MHZ, 24 * MHZ), Regulator::Ldo, false);
+
+        assert_eq!(
+            plan,
+            Some(Plan {
+                transition_mode: VCoreSel::LdoVcore1,
+                transition_wait: FlashWaitStates::_2,
+                final_mode: VCoreSel::LdoVcore1,
+                final_wait: FlashWaitStates::_2,
+            })
+        );
+    }
+
+    #[test]
+    fn slow_down() {
+        let current = clocks(48 * MHZ, 24 * MHZ);
+        let plan = Plan::new(&current, &clocks(12 * MHZ, 6 * MHZ), Regulator::Dcdc, false);
+
+        // The transition runs the current clocks, the final mode and wait states the requested ones
+        assert_eq!(
+            plan,
+            Some(Plan {
+                transition_mode: VCoreSel::DcdcVcore1,
+                transition_wait: FlashWaitStates::_2,
+                final_mode: VCoreSel::DcdcVcore0,
+                final_wait: FlashWaitStates::_0,
+            })
+        );
+    }
+
+    #[test]
+    fn low_frequency() {
+        let target = clocks(32_768, 32_768);
+
+        assert_eq!(
+            Plan::new(&reset(), &target, Regulator::Ldo, true).map(|plan| plan.final_mode),
+            Some(VCoreSel::LfVcore0)
+        );
+        // Only picked when enabled
+        assert_eq!(
+            Plan::new(&reset(), &target, Regulator::Ldo, false).map(|plan| plan.final_mode),
+            Some(VCoreSel::LdoVcore0)
+        );
+        // Too fast for the LF mode: the regulator mode is used instead
+        assert_eq!(
+            Plan::new(&reset(), &clocks(3 * MHZ, 3 * MHZ), Regulator::Dcdc, true)
+                .map(|plan| plan.final_mode),
+            Some(VCoreSel::DcdcVcore0)
+        );
+    }
+
+    #[test]
+    fn unreachable_clocks() {
+        let target = clocks(48 * MHZ, 48 * MHZ);
+        assert_eq!(Plan::new(&reset(), &target, Regulator::Ldo, false), None);
+
+        // The transition has to run the current clocks as well
+        let current = clocks(24 * MHZ, 48 * MHZ);
+        assert_eq!(Plan::new(&current, &clocks(48 * MHZ, 3 * MHZ), Regulator::Ldo, false), None);
+    }
+}
@@ -0,0 +1,714 @@
+//! HAL library for the Device Descriptor (TLV) table - MSP432P401R
+
+/*
+The Device Descriptor table lives in the information flash memory at 0x0020_1000 (see
+`sysctl::TLV_BASE`) and holds the factory calibration data of the device. It is a list of 32-bit
+little endian words:
+
+CHECKSUM --> CRC-32/ISO-HDLC of the table, from the first tag up to (and including) the end word;
+TAG --> Entry type;
+LENGTH --> Entry length, in 32-bit words;
+DATA --> LENGTH words;
+...
+END --> 0x0BD0E11D, marks the end of the table;
+
+-- TAGS: --
+
+0x03 --> Clock System (DCO frequency calibration and constants, for internal and external resistor);
+0x04 --> Flash Controller (maximum program and erase pulses);
+0x05 --> ADC14 (gain factors for each reference and buffer mode, offsets and temperature sensor
+         calibration for each reference);
+0x08 --> REF (reference voltages calibration);
+0x0B --> Device Info (device ID and revisions);
+0x0C --> Die Record (die position, wafer and lot);
+0x0D --> 128-bit Random Number;
+0x0F --> BSL (Bootstrap Loader) configuration;
+
+`Tlv` only borrows the bytes it parses, so it works on the table in flash (`Tlv::device()`) as
+well as on a copy of it (e.g. a dump taken from another device).
+*/
+
+use core::slice;
+
+use crate::crc::{Crc32Hasher, SoftCrc32};
+use crate::sysctl::TLV_BASE;
+
+/// Size in bytes of the MSP432P401R descriptor table
+pub const TLV_SIZE: usize = 0x14C;
+pub const TAG_END: u32 = 0x0BD0_E11D;
+
+const WORD: usize = 4;
+const HEADER: usize = 2 * WORD;
+
+const CS_IR_FCAL_RSEL04: usize = 0;
+const CS_IR_FCAL_RSEL5: usize = 1;
+const CS_IR_CONSTK_RSEL04: usize = 6;
+const CS_IR_CONSTK_RSEL5: usize = 7;
+const CS_ER_FCAL_RSEL04: usize = 8;
+const CS_ER_FCAL_RSEL5: usize = 9;
+const CS_ER_CONSTK_RSEL04: usize = 14;
+const CS_ER_CONSTK_RSEL5: usize = 15;
+
+const ADC_GAIN_FACTOR: usize = 0;
+const ADC_OFFSET_VRSEL_1: usize = 16;
+const ADC_OFFSET_VRSEL_14: usize = 17;
+const ADC_TEMPERATURE_SENSOR: usize = 18;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// The slice is too short or its length is not a multiple of 4
+    Length,
+    /// An entry overruns the table or the end word is missing
+    Malformed,
+    /// Stored and computed checksums differ
+    Checksum,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Tag {
+    ClockSystem,
+    FlashControl,
+    Adc14,
+    Reference,
+    DeviceInfo,
+    DieRecord,
+    RandomNumber,
+    Bsl,
+    /// Reserved or unknown tag
+    Other(u32),
+}
+
+impl From<u32> for Tag {
+    fn from(tag: u32) -> Self {
+        match tag {
+            0x03 => Tag::ClockSystem,
+            0x04 => Tag::FlashControl,
+            0x05 => Tag::Adc14,
+            0x08 => Tag::Reference,
+            0x0B => Tag::DeviceInfo,
+            0x0C => Tag::DieRecord,
+            0x0D => Tag::RandomNumber,
+            0x0F => Tag::Bsl,
+            tag => Tag::Other(tag),
+        }
+    }
+}
+
+/// One tag of the table and its data
+#[derive(Debug, Copy, Clone)]
+pub struct Entry<'a> {
+    tag: Tag,
+    data: &'a [u8],
+}
+
+impl<'a> Entry<'a> {
+    #[inline]
+    pub fn tag(&self) -> Tag {
+        self.tag
+    }
+
+    /// Raw data, without the tag and length words
+    #[inline]
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Number of data words
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.data.len() / WORD
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Data word at `index`
+    #[inline]
+    pub fn word(&self, index: usize) -> Option<u32> {
+        word(self.data, index * WORD)
+    }
+}
+
+/// Iterator over the entries of the table, stops at the end word or at the first malformed entry
+pub struct Entries<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Entry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (entry, next) = entry_at(self.data, self.offset)?;
+        self.offset = next;
+        Some(entry)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DeviceInfo {
+    pub device_id: u32,
+    pub hardware_revision: u32,
+    pub boot_code_revision: u32,
+    pub rom_driver_library_revision: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DieRecord {
+    pub x_position: u32,
+    pub y_position: u32,
+    pub wafer_id: u32,
+    pub lot_id: u32,
+}
+
+/// DCO resistor the calibration was made with
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DcoResistor {
+    Internal,
+    External,
+}
+
+/// Calibration of one DCO frequency range
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DcoConstants {
+    /// Frequency calibration (DCOFCAL)
+    pub fcal: u32,
+    /// DCO constant (K)
+    pub constk: f32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DcoCalibration {
+    /// DCORSEL 0 to 4
+    pub rsel04: DcoConstants,
+    /// DCORSEL 5
+    pub rsel5: DcoConstants,
+}
+
+/// ADC14 offsets, for each buffered positive reference
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AdcCalibration {
+    /// VR+ = VREF buffered (ADC14VRSEL = 1)
+    pub offset_vrsel_1: i32,
+    /// VR+ = VeREF+ buffered (ADC14VRSEL = 14)
+    pub offset_vrsel_14: i32,
+}
+
+/// Reference and buffer mode an ADC14 gain factor was calibrated with
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AdcGainMode {
+    /// External reference, unbuffered
+    External,
+    /// External reference, buffered
+    ExternalBuffered,
+    /// Internal reference (buffered), `refout` if it is also output on the REFOUT pin
+    Internal { reference: AdcReference, refout: bool },
+}
+
+impl AdcGainMode {
+    /// Index of the 30°C gain factor word, the 85°C one follows
+    fn index(self) -> usize {
+        ADC_GAIN_FACTOR
+            + match self {
+                AdcGainMode::External => 0,
+                AdcGainMode::ExternalBuffered => 2,
+                AdcGainMode::Internal { reference, refout } => {
+                    4 + 4 * reference as usize + 2 * refout as usize
+                }
+            }
+    }
+}
+
+/// ADC14 gain factors at 30°C and 85°C
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AdcGainFactor {
+    pub gf30: u32,
+    pub gf85: u32,
+}
+
+/// Internal reference voltage the gain factors and the temperature sensor were calibrated with
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AdcReference {
+    V1_2 = 0,
+    V1_45 = 1,
+    V2_5 = 2,
+}
+
+/// ADC14 readings of the temperature sensor at 30°C and 85°C
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TemperatureCalibration {
+    pub cal30: u32,
+    pub cal85: u32,
+}
+
+impl TemperatureCalibration {
+    /// Temperature in °C of an ADC14 temperature sensor conversion result
+    pub fn celsius(&self, sample: u16) -> f32 {
+        let slope = (85.0 - 30.0) / (self.cal85 as f32 - self.cal30 as f32);
+        (sample as f32 - self.cal30 as f32) * slope + 30.0
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ReferenceCalibration {
+    pub v1_2: u32,
+    pub v1_45: u32,
+    pub v2_5: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct FlashInfo {
+    pub max_program_pulses: u32,
+    pub max_erase_pulses: u32,
+}
+
+/// Bootstrap Loader configuration (raw words, see the BSL user's guide)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BslConfig {
+    pub interface_selection: u32,
+    pub uart_config: u32,
+    pub spi_config: u32,
+    pub i2c_config: u32,
+}
+
+/// Zero-copy parser of a Device Descriptor table
+#[derive(Debug, Copy, Clone)]
+pub struct Tlv<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    /// Wrap a table, the checksum is not verified
+    pub fn new(data: &'a [u8]) -> Result<Self, Error> {
+        if data.len() < WORD || data.len() % WORD != 0 {
+            return Err(Error::Length);
+        }
+
+        Ok(Tlv { data })
+    }
+
+    /// Wrap a table, failing if it is malformed or its checksum does not match
+    pub fn new_checked(data: &'a [u8]) -> Result<Self, Error> {
+        let tlv = Self::new(data)?;
+        tlv.verify()?;
+        Ok(tlv)
+    }
+
+    /// Table of this device, in the information flash memory
+    pub fn device() -> Tlv<'static> {
+        // The table is read-only and always mapped
+        Tlv {
+            data: unsafe { slice::from_raw_parts(TLV_BASE as *const u8, TLV_SIZE) },
+        }
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Stored checksum
+    #[inline]
+    pub fn checksum(&self) -> u32 {
+        word(self.data, 0).unwrap_or(0)
+    }
+
+    /// Checksum computed over the table, `None` if the end word can't be found
+    pub fn computed_checksum(&self) -> Option<u32> {
+        let end = self.end()?;
+        let mut crc = Crc32Hasher::new(SoftCrc32::new());
+
+        crc.update(&self.data[WORD..end]);
+        Some(crc.checksum())
+    }
+
+    /// Check the structure and the checksum of the table
+    pub fn verify(&self) -> Result<(), Error> {
+        match self.computed_checksum() {
+            None => Err(Error::Malformed),
+            Some(checksum) if checksum != self.checksum() => Err(Error::Checksum),
+            Some(_) => Ok(()),
+        }
+    }
+
+    #[inline]
+    pub fn entries(&self) -> Entries<'a> {
+        Entries {
+            data: self.data,
+            offset: WORD,
+        }
+    }
+
+    /// First entry with `tag`
+    pub fn find(&self, tag: Tag) -> Option<Entry<'a>> {
+        self.entries().find(|entry| entry.tag == tag)
+    }
+
+    pub fn device_info(&self) -> Option<DeviceInfo> {
+        let entry = self.find(Tag::DeviceInfo)?;
+
+        Some(DeviceInfo {
+            device_id: entry.word(0)?,
+            hardware_revision: entry.word(1)?,
+            boot_code_revision: entry.word(2)?,
+            rom_driver_library_revision: entry.word(3)?,
+        })
+    }
+
+    pub fn die_record(&self) -> Option<DieRecord> {
+        let entry = self.find(Tag::DieRecord)?;
+
+        Some(DieRecord {
+            x_position: entry.word(0)?,
+            y_position: entry.word(1)?,
+            wafer_id: entry.word(2)?,
+            lot_id: entry.word(3)?,
+        })
+    }
+
+    pub fn dco_calibration(&self, resistor: DcoResistor) -> Option<DcoCalibration> {
+        let entry = self.find(Tag::ClockSystem)?;
+
+        let (fcal04, fcal5, constk04, constk5) = match resistor {
+            DcoResistor::Internal => (
+                CS_IR_FCAL_RSEL04,
+                CS_IR_FCAL_RSEL5,
+                CS_IR_CONSTK_RSEL04,
+                CS_IR_CONSTK_RSEL5,
+            ),
+            DcoResistor::External => (
+                CS_ER_FCAL_RSEL04,
+                CS_ER_FCAL_RSEL5,
+                CS_ER_CONSTK_RSEL04,
+                CS_ER_CONSTK_RSEL5,
+            ),
+        };
+
+        Some(DcoCalibration {
+            rsel04: DcoConstants {
+                fcal: entry.word(fcal04)?,
+                constk: f32::from_bits(entry.word(constk04)?),
+            },
+            rsel5: DcoConstants {
+                fcal: entry.word(fcal5)?,
+                constk: f32::from_bits(entry.word(constk5)?),
+            },
+        })
+    }
+
+    pub fn adc_calibration(&self) -> Option<AdcCalibration> {
+        let entry = self.find(Tag::Adc14)?;
+
+        Some(AdcCalibration {
+            offset_vrsel_1: entry.word(ADC_OFFSET_VRSEL_1)? as i32,
+            offset_vrsel_14: entry.word(ADC_OFFSET_VRSEL_14)? as i32,
+        })
+    }
+
+    pub fn adc_gain_factor(&self, mode: AdcGainMode) -> Option<AdcGainFactor> {
+        let entry = self.find(Tag::Adc14)?;
+        let index = mode.index();
+
+        Some(AdcGainFactor {
+            gf30: entry.word(index)?,
+            gf85: entry.word(index + 1)?,
+        })
+    }
+
+    pub fn temperature_sensor(&self, reference: AdcReference) -> Option<TemperatureCalibration> {
+        let entry = self.find(Tag::Adc14)?;
+        let index = ADC_TEMPERATURE_SENSOR + 2 * reference as usize;
+
+        Some(TemperatureCalibration {
+            cal30: entry.word(index)?,
+            cal85: entry.word(index + 1)?,
+        })
+    }
+
+    pub fn reference_calibration(&self) -> Option<ReferenceCalibration> {
+        let entry = self.find(Tag::Reference)?;
+
+        Some(ReferenceCalibration {
+            v1_2: entry.word(0)?,
+            v1_45: entry.word(1)?,
+            v2_5: entry.word(2)?,
+        })
+    }
+
+    pub fn flash_info(&self) -> Option<FlashInfo> {
+        let entry = self.find(Tag::FlashControl)?;
+
+        Some(FlashInfo {
+            max_program_pulses: entry.word(0)?,
+            max_erase_pulses: entry.word(1)?,
+        })
+    }
+
+    /// Factory programmed 128-bit random number, unique to each device
+    pub fn random_seed(&self) -> Option<[u8; 16]> {
+        let entry = self.find(Tag::RandomNumber)?;
+        let mut seed = [0u8; 16];
+
+        seed.copy_from_slice(entry.data.get(..16)?);
+        Some(seed)
+    }
+
+    pub fn bsl_config(&self) -> Option<BslConfig> {
+        let entry = self.find(Tag::Bsl)?;
+
+        Some(BslConfig {
+            interface_selection: entry.word(0)?,
+            uart_config: entry.word(1)?,
+            spi_config: entry.word(2)?,
+            i2c_config: entry.word(3)?,
+        })
+    }
+
+    /// Offset right after the end word
+    fn end(&self) -> Option<usize> {
+        let mut offset = WORD;
+
+        loop {
+            if word(self.data, offset)? == TAG_END {
+                return Some(offset + WORD);
+            }
+            offset = entry_at(self.data, offset)?.1;
+        }
+    }
+}
+
+#[inline]
+fn word(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + WORD)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Entry starting at `offset` and the offset of the next one
+fn entry_at(data: &[u8], offset: usize) -> Option<(Entry<'_>, usize)> {
+    let tag = word(data, offset)?;
+    if tag == TAG_END {
+        return None;
+    }
+
+    let length = (word(data, offset + WORD)? as usize).checked_mul(WORD)?;
+    let start = offset + HEADER;
+    let end = start.checked_add(length)?;
+
+    Some((
+        Entry {
+            tag: Tag::from(tag),
+            data: data.get(start..end)?,
+        },
+        end,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    /// Table in the MSP432P401R layout: tags, lengths and offsets of the TI register map (see
+    /// `pac::tlv::RegisterBlock`), checksum computed over it. The calibration values are
+    /// illustrative, in the range of the TI datasheet.
+    const DUMP: [u32; TLV_SIZE / WORD] = [
+        0x28C3_8745,
+        0x0B, 4,
+        0x0000A000, 0x00000043, 0x00000101, 0x00010200,
+        0x0C, 8,
+        0x0000001A, 0x0000002C, 0x00000007, 0x00E3D2A1,
+        0xFFFFFFFF, 0xFFFFFFFF, 0xFFFFFFFF, 0x00000000,
+        0x03, 16,
+        0x000000A6, 0x0000009B, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x3A1E5E2F, 0x3A2B8B5C,
+        0x000000C2, 0x000000B8, 0x00000000, 0x00000000,
+        0x00000000, 0x00000000, 0x3B0E8F47, 0x3B1F2A0C,
+        0x05, 24,
+        0x00008012, 0x0000800E, 0x00008009, 0x00008005,
+        0x00007FF4, 0x00007FF1, 0x00007FF6, 0x00007FF3,
+        0x00008003, 0x00007FFE, 0x00008004, 0x00008000,
+        0x00007FEA, 0x00007FE5, 0x00007FEC, 0x00007FE8,
+        0xFFFFFFFB, 0x00000003, 0x00000ABC, 0x00000C61,
+        0x00000A10, 0x00000B7A, 0x000005F0, 0x000006A4,
+        0x08, 3,
+        0x00008001, 0x00007FF2, 0x00007FE8,
+        0x04, 2,
+        0x00000005, 0x00000032,
+        0x0D, 4,
+        0x11223344, 0x55667788, 0x99AABBCC, 0xDDEEFF00,
+        0x0F, 4,
+        0xFC48FFFF, 0xFC48FFFF, 0xFC48FFFF, 0xFC48FFFF,
+        TAG_END,
+    ];
+
+    /// Offset of the Die Record and ADC14 entry tags
+    const DIE_ENTRY: usize = 0x1C;
+    const ADC_ENTRY: usize = 0x8C;
+
+    fn dump() -> Vec<u8> {
+        let mut bytes: Vec<u8> = DUMP.iter().flat_map(|word| word.to_le_bytes()).collect();
+        // Erased flash after the end word
+        bytes.resize(TLV_SIZE, 0xFF);
+        bytes
+    }
+
+    #[test]
+    fn parse_dump() {
+        let bytes = dump();
+        let tlv = Tlv::new_checked(&bytes).unwrap();
+
+        assert_eq!(tlv.checksum(), 0x28C3_8745);
+        assert_eq!(tlv.entries().count(), 8);
+
+        let info = tlv.device_info().unwrap();
+        assert_eq!(info.device_id, 0xA000);
+        assert_eq!(info.hardware_revision, 0x43);
+
+        assert_eq!(tlv.die_record().unwrap().lot_id, 0x00E3_D2A1);
+
+        let dco = tlv.dco_calibration(DcoResistor::Internal).unwrap();
+        assert_eq!(dco.rsel04.fcal, 0xA6);
+        assert_eq!(dco.rsel5.constk, f32::from_bits(0x3A2B_8B5C));
+        let dco = tlv.dco_calibration(DcoResistor::External).unwrap();
+        assert_eq!(dco.rsel04.fcal, 0xC2);
+        assert_eq!(dco.rsel5.constk, f32::from_bits(0x3B1F_2A0C));
+
+        assert_eq!(tlv.flash_info(), Some(FlashInfo { max_program_pulses: 5, max_erase_pulses: 50 }));
+        assert_eq!(
+            tlv.adc_calibration(),
+            Some(AdcCalibration { offset_vrsel_1: -5, offset_vrsel_14: 3 })
+        );
+        assert_eq!(
+            tlv.adc_gain_factor(AdcGainMode::External),
+            Some(AdcGainFactor { gf30: 0x8012, gf85: 0x800E })
+        );
+        assert_eq!(
+            tlv.adc_gain_factor(AdcGainMode::ExternalBuffered),
+            Some(AdcGainFactor { gf30: 0x8009, gf85: 0x8005 })
+        );
+        let internal = |reference, refout| AdcGainMode::Internal { reference, refout };
+        assert_eq!(
+            tlv.adc_gain_factor(internal(AdcReference::V1_2, true)),
+            Some(AdcGainFactor { gf30: 0x7FF6, gf85: 0x7FF3 })
+        );
+        assert_eq!(
+            tlv.adc_gain_factor(internal(AdcReference::V1_45, false)),
+            Some(AdcGainFactor { gf30: 0x8003, gf85: 0x7FFE })
+        );
+        assert_eq!(
+            tlv.adc_gain_factor(internal(AdcReference::V2_5, true)),
+            Some(AdcGainFactor { gf30: 0x7FEC, gf85: 0x7FE8 })
+        );
+        assert_eq!(
+            tlv.temperature_sensor(AdcReference::V1_45),
+            Some(TemperatureCalibration { cal30: 0xA10, cal85: 0xB7A })
+        );
+        assert_eq!(tlv.reference_calibration().unwrap().v2_5, 0x7FE8);
+        assert_eq!(tlv.bsl_config().unwrap().i2c_config, 0xFC48_FFFF);
+        assert_eq!(
+            tlv.random_seed(),
+            Some([0x44, 0x33, 0x22, 0x11, 0x88, 0x77, 0x66, 0x55, 0xCC, 0xBB, 0xAA, 0x99, 0x00, 0xFF, 0xEE, 0xDD])
+        );
+    }
+
+    #[test]
+    fn temperature_conversion() {
+        let cal = TemperatureCalibration { cal30: 1000, cal85: 1550 };
+
+        assert_eq!(cal.celsius(1000), 30.0);
+        assert_eq!(cal.celsius(1550), 85.0);
+    }
+
+    #[test]
+    fn register_map_offsets() {
+        use core::mem::MaybeUninit;
+        use core::ptr::addr_of;
+        use pac::tlv::RegisterBlock;
+
+        let block = MaybeUninit::<RegisterBlock>::uninit();
+        let base = block.as_ptr();
+        // Offsets of the tag registers, no reference to the uninitialized block is created
+        let offset = |field: *const u8| field as usize - base as usize;
+        let tags = unsafe {
+            [
+                (Tag::DeviceInfo, offset(addr_of!((*base).device_info_tag).cast())),
+                (Tag::DieRecord, offset(addr_of!((*base).die_rec_tag).cast())),
+                (Tag::ClockSystem, offset(addr_of!((*base).cs_cal_tag).cast())),
+                (Tag::Adc14, offset(addr_of!((*base).adc14_cal_tag).cast())),
+                (Tag::Reference, offset(addr_of!((*base).ref_cal_tag).cast())),
+                (Tag::FlashControl, offset(addr_of!((*base).flash_info_tag).cast())),
+                (Tag::RandomNumber, offset(addr_of!((*base).random_num_tag).cast())),
+                (Tag::Bsl, offset(addr_of!((*base).bsl_cfg_tag).cast())),
+            ]
+        };
+        let end = unsafe { offset(addr_of!((*base).tlv_end).cast()) };
+
+        let bytes = dump();
+        let tlv = Tlv::new_checked(&bytes).unwrap();
+        for (entry, &(tag, offset)) in tlv.entries().zip(tags.iter()) {
+            assert_eq!(entry.tag(), tag);
+            assert_eq!(entry.data().as_ptr() as usize - bytes.as_ptr() as usize, offset + HEADER);
+        }
+        assert_eq!(tlv.end(), Some(end + WORD));
+        assert_eq!(end + WORD, TLV_SIZE);
+        assert_eq!(DIE_ENTRY, tags[1].1);
+        assert_eq!(ADC_ENTRY, tags[3].1);
+    }
+
+    #[test]
+    fn unknown_tag() {
+        let mut bytes = dump();
+        bytes[DIE_ENTRY..DIE_ENTRY + 4].copy_from_slice(&0x07u32.to_le_bytes());
+        let tlv = Tlv::new(&bytes).unwrap();
+        let entry = tlv.find(Tag::Other(0x07)).unwrap();
+
+        assert_eq!(entry.len(), 8);
+        assert_eq!(entry.word(3), Some(0x00E3_D2A1));
+        assert_eq!(entry.word(8), None);
+        assert_eq!(tlv.die_record(), None);
+        // Entries after the reserved one are still found
+        assert!(tlv.reference_calibration().is_some());
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = dump();
+
+        assert_eq!(Tlv::new(&[]).err(), Some(Error::Length));
+        assert_eq!(Tlv::new(&bytes[..6]).err(), Some(Error::Length));
+
+        // Cut in the middle of the ADC14 entry: the entries before it are still readable
+        let tlv = Tlv::new(&bytes[..ADC_ENTRY + 4 * 10]).unwrap();
+        assert_eq!(tlv.verify(), Err(Error::Malformed));
+        assert!(tlv.device_info().is_some());
+        assert!(tlv.dco_calibration(DcoResistor::External).is_some());
+        assert_eq!(tlv.adc_calibration(), None);
+        assert_eq!(tlv.flash_info(), None);
+        assert_eq!(tlv.entries().count(), 3);
+
+        // No end word
+        let tlv = Tlv::new(&bytes[..4 * (DUMP.len() - 1)]).unwrap();
+        assert_eq!(tlv.verify(), Err(Error::Malformed));
+        assert_eq!(tlv.entries().count(), 8);
+    }
+
+    #[test]
+    fn corrupt() {
+        let mut bytes = dump();
+        bytes[ADC_ENTRY + 8] ^= 0x01;
+        assert_eq!(Tlv::new_checked(&bytes).err(), Some(Error::Checksum));
+
+        // An overrunning length (even one overflowing the offsets) ends the table
+        for length in [0x100, u32::MAX / 4 + 1, u32::MAX] {
+            let mut bytes = dump();
+            bytes[ADC_ENTRY + 4..ADC_ENTRY + 8].copy_from_slice(&length.to_le_bytes());
+            let tlv = Tlv::new(&bytes).unwrap();
+
+            assert_eq!(tlv.verify(), Err(Error::Malformed));
+            assert_eq!(tlv.adc_calibration(), None);
+            assert_eq!(tlv.entries().count(), 3);
+        }
+    }
+}